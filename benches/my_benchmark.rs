@@ -0,0 +1,44 @@
+use std::ops::ControlFlow;
+use std::path::PathBuf;
+
+use bedlam_cube::puzzle::{Coord, Puzzle};
+use bedlam_cube::solver::{Backtracker, Solver};
+use bedlam_cube::target::Target;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+fn count_solutions(puzzle: &Puzzle, strategy: &Backtracker) -> usize {
+    let mut solutions = 0;
+    let _ = Solver::build().begin(puzzle, strategy, &mut |_| {
+        solutions += 1;
+        ControlFlow::Continue(())
+    });
+    solutions
+}
+
+fn backtracker(c: &mut Criterion) {
+    let soma = Puzzle::from_csv(
+        PathBuf::from("puzzles/soma-cube.csv"),
+        &Target::cuboid(Coord::new(3, 3, 3)),
+        false,
+    )
+    .unwrap();
+    let pentominoes = Puzzle::from_csv(
+        PathBuf::from("puzzles/pentominoes.csv"),
+        &Target::from_file(PathBuf::from("puzzles/pentominoes-8x8-hole.txt")).unwrap(),
+        false,
+    )
+    .unwrap();
+    let strategy = Backtracker::default();
+
+    c.bench_function("soma cube", |b| b.iter(|| count_solutions(&soma, &strategy)));
+    c.bench_function("pentominoes 8x8 hole", |b| {
+        b.iter(|| count_solutions(&pentominoes, &strategy))
+    });
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(20);
+    targets = backtracker
+}
+criterion_main!(benches);
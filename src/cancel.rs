@@ -0,0 +1,39 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Cooperative stop signal for a search. Clones share the signal: once any of them is cancelled,
+/// every worker of the search stops at its next search node.
+#[derive(Clone, Debug)]
+pub struct CancellationToken {
+    /// The token's own flag, followed by those of the tokens it was derived from
+    flags: Vec<Arc<AtomicBool>>,
+}
+
+impl CancellationToken {
+    pub fn new() -> CancellationToken {
+        CancellationToken {
+            flags: vec![Arc::new(AtomicBool::new(false))],
+        }
+    }
+
+    /// Token that is cancelled along with this one, but can also be cancelled on its own
+    pub fn child(&self) -> CancellationToken {
+        let mut flags = CancellationToken::new().flags;
+        flags.extend(self.flags.iter().cloned());
+        CancellationToken { flags }
+    }
+
+    pub fn cancel(&self) {
+        self.flags[0].store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.flags.iter().any(|flag| flag.load(Ordering::Relaxed))
+    }
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        CancellationToken::new()
+    }
+}
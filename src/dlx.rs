@@ -0,0 +1,227 @@
+use crate::cancel::CancellationToken;
+use crate::progress::Progress;
+use crate::puzzle::{Arrangement, Placement, Puzzle};
+use crate::solution::{Solution, SolutionSink};
+use crate::solver::SolveStrategy;
+use crate::stats::Statistics;
+
+use std::ops::ControlFlow;
+
+/// Search strategy that solves the exact cover matrix of each starting arrangement with
+/// Dancing Links
+pub struct DancingLinks;
+
+impl<const W: usize> SolveStrategy<W> for DancingLinks {
+    fn solve(
+        &self,
+        puzzle: &Puzzle<W>,
+        start: &Arrangement<W>,
+        remaining: &[usize],
+        found: &mut SolutionSink<W>,
+        stats: &mut Statistics,
+        cancel: &CancellationToken,
+        progress: &Progress,
+    ) -> ControlFlow<()> {
+        progress.begin_branches(1);
+        let mut dlx = Dlx::build(puzzle, start, remaining);
+        let flow = dlx.search(cancel, &mut |placements| {
+            let mut solution = start.placements.clone();
+            solution.extend_from_slice(placements);
+            found(&Solution::new(solution))
+        });
+        stats.nodes += dlx.nodes;
+        progress.finish_branch(dlx.nodes);
+        flow
+    }
+}
+
+/// Exact cover matrix solved with Knuth's Dancing Links (Algorithm X). There is one column for
+/// every free cell of the target and one for every remaining piece, and one row for every
+/// placement of a remaining piece that fits on the starting arrangement.
+pub struct Dlx<const W: usize> {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    column: Vec<usize>,
+    size: Vec<usize>,
+    row: Vec<usize>,
+    rows: Vec<(usize, Placement<W>)>,
+    /// Search nodes visited so far
+    pub nodes: u64,
+}
+
+impl<const W: usize> Dlx<W> {
+    const ROOT: usize = 0;
+
+    pub fn build(puzzle: &Puzzle<W>, arrangement: &Arrangement<W>, remaining: &[usize]) -> Dlx<W> {
+        let cells = puzzle.dim.volume();
+        let mut cell_columns = vec![None; cells];
+        let mut columns = 0;
+        for (cell, column) in cell_columns.iter_mut().enumerate() {
+            if !arrangement.occupied.get(cell) {
+                columns += 1;
+                *column = Some(columns);
+            }
+        }
+        let piece_columns: Vec<usize> = (1..=remaining.len()).map(|i| columns + i).collect();
+        columns += remaining.len();
+
+        // Column headers are nodes 1..=columns, linked in a ring through the root
+        let headers = columns + 1;
+        let mut dlx = Dlx {
+            left: (0..headers).map(|i| (i + headers - 1) % headers).collect(),
+            right: (0..headers).map(|i| (i + 1) % headers).collect(),
+            up: (0..headers).collect(),
+            down: (0..headers).collect(),
+            column: (0..headers).collect(),
+            size: vec![0; headers],
+            row: vec![usize::MAX; headers],
+            rows: vec![],
+            nodes: 0,
+        };
+
+        for (idx, &pid) in remaining.iter().enumerate() {
+            for &placement in puzzle.pieces[pid].placements() {
+                if arrangement.occupied.intersects(placement) {
+                    continue;
+                }
+                let mut row_columns: Vec<usize> = (0..cells)
+                    .filter(|&cell| placement.get(cell))
+                    .map(|cell| cell_columns[cell].unwrap())
+                    .collect();
+                row_columns.push(piece_columns[idx]);
+                dlx.add_row(&row_columns);
+                dlx.rows.push((pid, placement));
+            }
+        }
+
+        dlx
+    }
+
+    fn add_row(&mut self, columns: &[usize]) {
+        let row = self.rows.len();
+        let first = self.left.len();
+        for (i, &c) in columns.iter().enumerate() {
+            let node = first + i;
+            // Link into the row ring
+            self.left.push(if i == 0 { first + columns.len() - 1 } else { node - 1 });
+            self.right.push(if i == columns.len() - 1 { first } else { node + 1 });
+            // Link at the bottom of the column
+            self.up.push(self.up[c]);
+            self.down.push(c);
+            let last = self.up[c];
+            self.down[last] = node;
+            self.up[c] = node;
+            self.column.push(c);
+            self.row.push(row);
+            self.size[c] += 1;
+        }
+    }
+
+    fn cover(&mut self, c: usize) {
+        self.right[self.left[c]] = self.right[c];
+        self.left[self.right[c]] = self.left[c];
+        let mut i = self.down[c];
+        while i != c {
+            let mut j = self.right[i];
+            while j != i {
+                self.down[self.up[j]] = self.down[j];
+                self.up[self.down[j]] = self.up[j];
+                self.size[self.column[j]] -= 1;
+                j = self.right[j];
+            }
+            i = self.down[i];
+        }
+    }
+
+    fn uncover(&mut self, c: usize) {
+        let mut i = self.up[c];
+        while i != c {
+            let mut j = self.left[i];
+            while j != i {
+                self.size[self.column[j]] += 1;
+                self.down[self.up[j]] = j;
+                self.up[self.down[j]] = j;
+                j = self.left[j];
+            }
+            i = self.up[i];
+        }
+        self.right[self.left[c]] = c;
+        self.left[self.right[c]] = c;
+    }
+
+    /// Column with the fewest remaining rows (minimum remaining values)
+    fn choose_column(&self) -> usize {
+        let mut best = self.right[Self::ROOT];
+        let mut c = self.right[best];
+        while c != Self::ROOT {
+            if self.size[c] < self.size[best] {
+                best = c;
+            }
+            c = self.right[c];
+        }
+        best
+    }
+
+    /// Finds every exact cover, calling `found` with the placements of each one until it breaks
+    /// or `cancel` is cancelled
+    pub fn search<F>(&mut self, cancel: &CancellationToken, found: &mut F) -> ControlFlow<()>
+    where
+        F: FnMut(&[(usize, Placement<W>)]) -> ControlFlow<()>,
+    {
+        let mut chosen = vec![];
+        self.search_from(cancel, &mut chosen, found)
+    }
+
+    fn search_from<F>(
+        &mut self,
+        cancel: &CancellationToken,
+        chosen: &mut Vec<(usize, Placement<W>)>,
+        found: &mut F,
+    ) -> ControlFlow<()>
+    where
+        F: FnMut(&[(usize, Placement<W>)]) -> ControlFlow<()>,
+    {
+        if cancel.is_cancelled() {
+            return ControlFlow::Break(());
+        }
+        self.nodes += 1;
+        if self.right[Self::ROOT] == Self::ROOT {
+            return found(chosen);
+        }
+
+        let c = self.choose_column();
+        if self.size[c] == 0 {
+            return ControlFlow::Continue(());
+        }
+
+        self.cover(c);
+        let mut r = self.down[c];
+        while r != c {
+            let mut j = self.right[r];
+            while j != r {
+                self.cover(self.column[j]);
+                j = self.right[j];
+            }
+            chosen.push(self.rows[self.row[r]]);
+
+            let flow = self.search_from(cancel, chosen, found);
+
+            chosen.pop();
+            let mut j = self.left[r];
+            while j != r {
+                self.uncover(self.column[j]);
+                j = self.left[j];
+            }
+            if flow.is_break() {
+                // Leave the matrix as it was built
+                self.uncover(c);
+                return flow;
+            }
+            r = self.down[r];
+        }
+        self.uncover(c);
+        ControlFlow::Continue(())
+    }
+}
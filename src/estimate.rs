@@ -0,0 +1,46 @@
+/// Mean of a set of samples, with its standard error
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Mean {
+    pub mean: f64,
+    /// Standard deviation of the samples divided by the square root of their number, infinite
+    /// for fewer than two samples
+    pub std_error: f64,
+}
+
+impl Mean {
+    pub fn of(samples: &[f64]) -> Mean {
+        let n = samples.len() as f64;
+        if samples.len() < 2 {
+            return Mean {
+                mean: samples.first().copied().unwrap_or(0.0),
+                std_error: f64::INFINITY,
+            };
+        }
+        let mean = samples.iter().sum::<f64>() / n;
+        let variance = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0);
+        Mean {
+            mean,
+            std_error: (variance / n).sqrt(),
+        }
+    }
+
+    /// Approximate 95% confidence interval, clamped at zero since the estimated counts cannot be
+    /// negative
+    pub fn interval(&self) -> (f64, f64) {
+        let margin = 1.96 * self.std_error;
+        ((self.mean - margin).max(0.0), self.mean + margin)
+    }
+}
+
+/// Size of a search estimated from random probes of Knuth's estimator (see `Solver::estimate`)
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Estimate {
+    /// Number of probes the estimate is based on
+    pub probes: usize,
+    /// Search nodes the full search would visit
+    pub nodes: Mean,
+    /// Solutions the full search would find
+    pub solutions: Mean,
+    /// Duration of the full search on one thread
+    pub seconds: Mean,
+}
@@ -1,4 +1,15 @@
 #![feature(portable_simd)]
 
+pub mod cancel;
+pub mod dlx;
+pub mod estimate;
+pub mod progress;
 pub mod puzzle;
+pub mod random;
+pub mod shard;
+pub mod solution;
 pub mod solver;
+pub mod stats;
+pub mod symmetry;
+pub mod target;
+pub mod trace;
@@ -1,31 +1,832 @@
-use std::io;
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, IsTerminal, Write};
+use std::ops::ControlFlow;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
 
-use bedlam_cube::puzzle::Puzzle;
-use bedlam_cube::solver::Solver;
-use clap::Parser;
+use bedlam_cube::puzzle::{self, Coord, Puzzle};
+use bedlam_cube::random::Reservoir;
+use bedlam_cube::dlx::DancingLinks;
+use bedlam_cube::progress::Progress;
+use bedlam_cube::shard::{Shard, SolutionFile};
+use bedlam_cube::solution::Solution;
+use bedlam_cube::solver::{Algorithm, Backtracker, CellOrder, MoveOrder, SolveStrategy, Solver};
+use bedlam_cube::stats::{Report, Statistics};
+use bedlam_cube::target::Target;
+use clap::{Parser, Subcommand};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
-struct Args {
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    puzzle: Option<PuzzleArgs>,
+
+    #[command(flatten)]
+    args: Args,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Combine the solution files written by every shard of a run
+    Merge {
+        /// Solution files, one per shard
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+
+        /// File to write the merged solutions to
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Estimate the number of nodes and solutions of the bitset backtracker's search, and how
+    /// long it would take, from random probes of the search tree
+    Estimate {
+        #[command(flatten)]
+        puzzle: PuzzleArgs,
+
+        /// Order in which the bitset backtracker fills empty cells
+        #[arg(short, long, value_enum, default_value_t = CellOrder::Linear)]
+        cell_order: CellOrder,
+
+        /// Do not reject placements that leave a region of empty cells the remaining pieces
+        /// cannot fill
+        #[arg(long)]
+        no_region_pruning: bool,
+
+        /// Number of random probes
+        #[arg(short = 'n', long, default_value_t = 1000, value_parser = clap::value_parser!(u64).range(1..))]
+        probes: u64,
+
+        /// Seed of the random probes
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+    },
+    /// Print solutions drawn uniformly at random from every solution of the puzzle
+    Sample {
+        #[command(flatten)]
+        puzzle: PuzzleArgs,
+
+        /// Number of solutions to draw
+        #[arg(short = 'n', long, default_value_t = 1)]
+        count: usize,
+
+        /// Seed of the random draw
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+
+        /// Draw from one solution of each class of solutions related by a rotation
+        #[arg(short, long)]
+        unique: bool,
+
+        /// Draw from one solution of each class of solutions related by a rotation or a
+        /// reflection
+        #[arg(long)]
+        mirror_equivalent: bool,
+    },
+}
+
+#[derive(clap::Args)]
+struct PuzzleArgs {
     /// Puzzle file
     puzzle: PathBuf,
 
+    /// Box dimensions as XxYxZ, e.g. 2x4x8 (at most 512 cells)
     #[arg(short, default_value = "4x4x4")]
     size: String,
 
+    /// Target shape file, as layers of `#` (cell) and `.` (empty) separated by blank lines.
+    /// The box dimensions are taken from the file.
+    #[arg(short, long, conflicts_with = "size")]
+    target: Option<PathBuf>,
+
+    /// Allow chiral pieces to be placed as their mirror image
+    #[arg(long)]
+    reflected_pieces: bool,
+}
+
+impl PuzzleArgs {
+    fn target(&self) -> io::Result<Target> {
+        match &self.target {
+            Some(path) => Target::from_file(path.clone()),
+            None => {
+                let dim: Coord = self
+                    .size
+                    .parse()
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+                // Checked before the target lists every cell of the box
+                if dim.checked_volume().is_none_or(|cells| cells > puzzle::MAX_CELLS) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("{} box has more than {} cells", self.size, puzzle::MAX_CELLS),
+                    ));
+                }
+                Ok(Target::cuboid(dim))
+            }
+        }
+    }
+
+    /// Loads the puzzle, printing the target it is solved for
+    fn load<const W: usize>(&self, target: &Target) -> io::Result<Puzzle<W>> {
+        let puzzle = Puzzle::<W>::from_csv(self.puzzle.clone(), target, self.reflected_pieces)?;
+        match &self.target {
+            Some(path) => println!("{:?}", path),
+            None => println!("{:?}", self.size),
+        }
+        Ok(puzzle)
+    }
+
+    /// Puzzle, target and the options that change the placements, as recorded in solution files
+    /// so that only files of the same search are merged or resumed. The pieces and target are
+    /// identified by a hash of the piece file and the target cells rather than by their paths,
+    /// which can differ between machines running the same search.
+    fn description(&self, target: &Target) -> io::Result<String> {
+        let pieces = fs::read(&self.puzzle)?;
+        let cells: Vec<u8> = target
+            .cells
+            .iter()
+            .flat_map(|cell| [cell.x, cell.y, cell.z])
+            .flat_map(i64::to_le_bytes)
+            .collect();
+        let dim = target.dim;
+        let mut description = format!(
+            "{}x{}x{} pieces {:016x} target {:016x}",
+            dim.x,
+            dim.y,
+            dim.z,
+            fnv1a(&pieces),
+            fnv1a(&cells)
+        );
+        if self.reflected_pieces {
+            description += " --reflected-pieces";
+        }
+        Ok(description)
+    }
+}
+
+/// 64-bit FNV-1a hash, which unlike `DefaultHasher` is the same on every machine and Rust version
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(0xcbf29ce484222325, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
+
+#[derive(clap::Args)]
+struct Args {
+    /// Search strategy
+    #[arg(short, long, value_enum, default_value_t = Algorithm::Bitset)]
+    algorithm: Algorithm,
+
+    /// Order in which the bitset backtracker fills empty cells
+    #[arg(short, long, value_enum, default_value_t = CellOrder::Linear)]
+    cell_order: CellOrder,
+
+    /// Order in which the bitset backtracker tries placements
+    #[arg(short, long, value_enum, default_value_t = MoveOrder::Natural)]
+    move_order: MoveOrder,
+
+    /// Number of moves the entropy move ordering looks ahead
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    lookahead: u64,
+
+    /// Do not reject placements that leave a region of empty cells the remaining pieces cannot
+    /// fill
+    #[arg(long)]
+    no_region_pruning: bool,
+
+    /// Print only one solution from each class of solutions related by a rotation
+    #[arg(short, long)]
+    unique: bool,
+
+    /// Print only one solution from each class of solutions related by a rotation or a
+    /// reflection
+    #[arg(long)]
+    mirror_equivalent: bool,
+
+    /// Number of pieces the bitset backtracker places before splitting the search into tasks
+    /// shared between threads, 0 to search each starting arrangement on one thread
+    #[arg(long, default_value_t = 2)]
+    split_depth: usize,
+
+    /// Number of worker threads, 0 for one per core
+    #[arg(short = 'j', long, default_value_t = 0)]
+    threads: usize,
+
+    /// Only search shard i of n of the work units, e.g. 2/8
+    #[arg(long, conflicts_with_all = ["unique", "mirror_equivalent"])]
+    shard: Option<Shard>,
+
+    /// File to write the solutions to, one per line, for `merge`
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// File to save the progress of the search to, to continue it later with --resume
+    #[arg(long)]
+    checkpoint: Option<PathBuf>,
+
+    /// Seconds between checkpoints. Each checkpoint is saved once the work unit in progress is
+    /// finished.
+    #[arg(long, default_value_t = 60)]
+    checkpoint_interval: u64,
+
+    /// Continue the search saved in a checkpoint
+    #[arg(long)]
+    resume: Option<PathBuf>,
+
+    /// Stop after printing this many solutions
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    max_solutions: Option<u64>,
+
+    /// Stop after printing the first solution
+    #[arg(long, conflicts_with = "max_solutions")]
+    first: bool,
+
+    /// Stop the search after this long, e.g. 90s, 5m or 1h30m (plain numbers are seconds)
+    #[arg(long, value_parser = parse_duration)]
+    timeout: Option<Duration>,
+
+    /// Print a report of the nodes the bitset backtracker visited at each depth, the placements
+    /// rejected by each check and the time spent on each starting arrangement
+    #[arg(long, value_enum)]
+    report: Option<ReportFormat>,
+
+    /// File to write the report to instead of standard output, e.g. to read the JSON report
+    /// from a script
+    #[arg(long, requires = "report")]
+    report_file: Option<PathBuf>,
+
+    /// File to write the search tree of the bitset backtracker to, as a Graphviz graph, once the
+    /// search has finished
+    #[arg(long, conflicts_with = "shard")]
+    trace_dot: Option<PathBuf>,
+
+    /// Number of pieces past each starting arrangement to draw in the search tree
+    #[arg(long, requires = "trace_dot")]
+    trace_depth: Option<usize>,
+
+    /// Maximum number of nodes to draw in the search tree
+    #[arg(long, default_value_t = 1000, requires = "trace_dot")]
+    trace_nodes: usize,
+
     /// Verbose output
     #[arg(short, long)]
     verbose: bool,
 }
 
-fn main() -> io::Result<()> {
-    let args = Args::parse();
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+enum ReportFormat {
+    /// Human-readable tables
+    Table,
+    /// One line of JSON
+    Json,
+}
+
+/// Parses a duration such as `90`, `90s`, `500ms`, `5m` or `1h30m`
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    let invalid = || format!("invalid duration {s:?}, expected e.g. 90s, 5m or 1h30m");
+    let is_number = |c: char| c.is_ascii_digit() || c == '.';
+    let mut rest = s.trim();
+    if rest.is_empty() {
+        return Err(invalid());
+    }
+    let mut seconds = 0.0;
+    while !rest.is_empty() {
+        let (number, tail) = rest.split_at(rest.find(|c| !is_number(c)).unwrap_or(rest.len()));
+        let (unit, tail) = tail.split_at(tail.find(is_number).unwrap_or(tail.len()));
+        let value: f64 = number.parse().map_err(|_| invalid())?;
+        let scale = match unit {
+            "" | "s" => 1.0,
+            "ms" => 0.001,
+            "m" => 60.0,
+            "h" => 3600.0,
+            "d" => 86400.0,
+            _ => return Err(invalid()),
+        };
+        seconds += value * scale;
+        rest = tail;
+    }
+    Duration::try_from_secs_f64(seconds).map_err(|_| invalid())
+}
+
+/// Formats a count with a metric suffix, e.g. 1.5M
+fn format_count(count: f64) -> String {
+    match count {
+        c if c < 1e3 => format!("{:.0}", c),
+        c if c < 1e6 => format!("{:.1}k", c / 1e3),
+        c if c < 1e9 => format!("{:.1}M", c / 1e6),
+        c => format!("{:.1}G", c / 1e9),
+    }
+}
+
+/// Redraws a line on stderr with the progress of the search twice a second, until `stop` is
+/// dropped. `terminal` is held while the line is drawn, so that other output can clear it first.
+fn show_progress(
+    progress: Arc<Progress>,
+    terminal: Arc<Mutex<()>>,
+    stop: mpsc::Receiver<()>,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        let start_time = Instant::now();
+        // Fraction already done at the first redraw, e.g. when resuming a checkpoint, and when that
+        // was, so that the remaining time is projected from the progress made since
+        let mut initial = None;
+        while let Err(mpsc::RecvTimeoutError::Timeout) = stop.recv_timeout(Duration::from_millis(500)) {
+            let seconds = start_time.elapsed().as_secs_f64();
+            let fraction = progress.fraction();
+            let (initial_fraction, initial_time) = *initial.get_or_insert((fraction, Instant::now()));
+            let eta = match fraction - initial_fraction {
+                done if done > 0.0 => {
+                    format_seconds(initial_time.elapsed().as_secs_f64() * (1.0 - fraction) / done)
+                }
+                _ => "?".to_string(),
+            };
+            let _terminal = terminal.lock().unwrap();
+            eprint!(
+                "\r\x1b[Kstart {}/{}, {:.1}% done, {} solutions/s, {} nodes/s, ETA {}",
+                (progress.start.load(Ordering::Relaxed) + 1).min(progress.starts.load(Ordering::Relaxed)),
+                progress.starts.load(Ordering::Relaxed),
+                fraction * 100.0,
+                format_count(progress.solutions.load(Ordering::Relaxed) as f64 / seconds),
+                format_count(progress.nodes.load(Ordering::Relaxed) as f64 / seconds),
+                eta
+            );
+        }
+        let _terminal = terminal.lock().unwrap();
+        eprint!("\r\x1b[K");
+    })
+}
 
-    let puzzle = Puzzle::from_csv(args.puzzle, &args.size)?;
-    println!("{:?}", args.size);
+fn solve<const W: usize>(puzzle_args: &PuzzleArgs, args: &Args, target: &Target) -> io::Result<()> {
+    let puzzle = puzzle_args.load::<W>(target)?;
 
+    let start_time = Instant::now();
     let mut solver = Solver::build();
-    solver.begin(&puzzle);
+    let constrained_piece = solver.constrained_piece(&puzzle);
+    println!("{:?}", (constrained_piece, &puzzle.pieces[constrained_piece]));
+
+    // Solutions filtered by symmetry cannot be combined with unfiltered ones
+    let mut description = puzzle_args.description(target)?;
+    if args.mirror_equivalent {
+        description += " --mirror-equivalent";
+    } else if args.unique {
+        description += " --unique";
+    }
+    let shard = args.shard.unwrap_or(Shard::ALL);
+    let resume = match &args.resume {
+        Some(path) => {
+            let checkpoint = SolutionFile::read(path.clone())?;
+            if checkpoint.completed_units.is_none() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{} is not a checkpoint", path.display()),
+                ));
+            }
+            if checkpoint.puzzle != description || checkpoint.shard != Some(shard) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "{} is a checkpoint of {} shard {}",
+                        path.display(),
+                        checkpoint.puzzle,
+                        checkpoint.shard.unwrap_or(Shard::ALL)
+                    ),
+                ));
+            }
+            Some(checkpoint)
+        }
+        None => None,
+    };
+
+    let mut output = match &args.output {
+        Some(path) => {
+            let mut output = BufWriter::new(File::create(path)?);
+            SolutionFile::write_header(&mut output, &description, shard)?;
+            Some(output)
+        }
+        None => None,
+    };
+    let mut written = Ok(());
+    // Work unit being searched, and the one of the last solution written to the output, so that
+    // `merge` can put the solutions of interleaved shards back in order
+    let unit = AtomicUsize::new(shard.unit(0));
+    let mut last_unit = 0;
+    // Solutions passed on so far, with their work units, for checkpoints
+    let emitted = Mutex::new(vec![]);
+
+    let mut solutions = 0;
+    let max_solutions = if args.first { Some(1) } else { args.max_solutions.map(|max| max as usize) };
+    let mut seen = HashSet::new();
+    let backtracker = Backtracker {
+        cell_order: args.cell_order,
+        move_order: args.move_order,
+        lookahead: args.lookahead as usize,
+        prune_isolated: !args.no_region_pruning,
+        split_depth: args.split_depth,
+        instrument: args.report.is_some(),
+    };
+    let strategy: Box<dyn SolveStrategy<W>> = match args.algorithm {
+        Algorithm::Bitset => Box::new(backtracker),
+        Algorithm::Dlx => Box::new(DancingLinks),
+    };
+    // Held while printing, so that solutions do not mix with the progress line
+    let terminal = Arc::new(Mutex::new(()));
+    let show_progress_line = io::stdout().is_terminal();
+    let mut found = |solution: &Solution<W>| {
+        let unique = args.unique || args.mirror_equivalent;
+        if unique && !seen.insert(solution.canonical(&puzzle, args.mirror_equivalent)) {
+            return ControlFlow::Continue(());
+        }
+        let _terminal = terminal.lock().unwrap();
+        if show_progress_line {
+            eprint!("\r\x1b[K");
+        }
+        puzzle.show(solution);
+        println!();
+        solutions += 1;
+        let line = solution.to_line(&puzzle);
+        let unit = unit.load(Ordering::Relaxed);
+        if let Some(output) = &mut output {
+            written = SolutionFile::write_unit(output, &mut last_unit, unit).and_then(|_| writeln!(output, "{}", line));
+            if written.is_err() {
+                return ControlFlow::Break(());
+            }
+        }
+        if args.checkpoint.is_some() {
+            emitted.lock().unwrap().push((unit, line));
+        }
+        if max_solutions.is_some_and(|max| solutions >= max) {
+            return ControlFlow::Break(());
+        }
+        ControlFlow::Continue(())
+    };
+
+    // Work units finished, solutions passed on by then and statistics, as of the last finished
+    // unit, so that a checkpoint never includes part of a unit. None until a unit finishes
+    // after resuming, as the replayed solutions may stop the run before they are all passed on.
+    let mut finished = resume.is_none().then(|| (0, 0, Statistics::default()));
+    let save_checkpoint = |path: &PathBuf, (completed, solutions, stats): (usize, usize, Statistics)| {
+        let checkpoint = SolutionFile {
+            puzzle: description.clone(),
+            shard: Some(shard),
+            solutions: emitted.lock().unwrap()[..solutions].to_vec(),
+            stats,
+            completed_units: Some(completed),
+        };
+        checkpoint.save(path)
+    };
+    let mut last_checkpoint = Instant::now();
+    let mut saved = Ok(());
+    let mut unit_done = |completed: usize, stats: &Statistics| {
+        unit.store(shard.unit(completed), Ordering::Relaxed);
+        let Some(path) = &args.checkpoint else {
+            return ControlFlow::Continue(());
+        };
+        let state = (completed, emitted.lock().unwrap().len(), *stats);
+        finished = Some(state);
+        if last_checkpoint.elapsed() < Duration::from_secs(args.checkpoint_interval) {
+            return ControlFlow::Continue(());
+        }
+        saved = save_checkpoint(path, state);
+        last_checkpoint = Instant::now();
+        if saved.is_err() {
+            return ControlFlow::Break(());
+        }
+        ControlFlow::Continue(())
+    };
+
+    if let Some(timeout) = args.timeout {
+        let cancel = solver.cancel.clone();
+        thread::spawn(move || {
+            thread::sleep(timeout);
+            cancel.cancel();
+        });
+    }
+    let (stop_progress, stop) = mpsc::channel();
+    let progress_line =
+        show_progress_line.then(|| show_progress(Arc::clone(&solver.progress), Arc::clone(&terminal), stop));
+    let flow = match &resume {
+        Some(checkpoint) => {
+            // Solutions found before the checkpoint go through the sink again, so that they are
+            // printed, written and counted as in an uninterrupted run
+            let mut flow = ControlFlow::Continue(());
+            for (solution_unit, line) in &checkpoint.solutions {
+                unit.store(*solution_unit, Ordering::Relaxed);
+                let solution = Solution::from_line(line, &puzzle).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidData, format!("invalid solution {line:?}"))
+                })?;
+                flow = found(&solution);
+                if flow.is_break() {
+                    break;
+                }
+            }
+            solver.stats = checkpoint.stats;
+            let completed = checkpoint.completed_units.unwrap_or(0);
+            unit.store(shard.unit(completed), Ordering::Relaxed);
+            if flow.is_break() {
+                flow
+            } else {
+                solver.resume_shard(&puzzle, strategy.as_ref(), shard, completed, &mut found, &mut unit_done)
+            }
+        }
+        None if args.shard.is_some() || args.checkpoint.is_some() => {
+            solver.resume_shard(&puzzle, strategy.as_ref(), shard, 0, &mut found, &mut unit_done)
+        }
+        None => solver.begin(&puzzle, strategy.as_ref(), &mut found),
+    };
+    drop(stop_progress);
+    if let Some(progress_line) = progress_line {
+        let _ = progress_line.join();
+    }
+    written?;
+    saved?;
+    if let (Some(path), true) = (&args.checkpoint, flow.is_break()) {
+        // Keep the units finished since the last checkpoint of a run stopped early, or the
+        // checkpoint it resumed if it finished none
+        match (finished, &resume) {
+            (Some(state), _) => save_checkpoint(path, state)?,
+            (None, Some(checkpoint)) => checkpoint.save(path)?,
+            (None, None) => {}
+        }
+    }
+    // The trace searches again from the start, which a search stopped early should not do
+    if let (Some(path), true) = (&args.trace_dot, flow.is_continue()) {
+        let trace = solver.trace(&puzzle, &backtracker, args.trace_depth.unwrap_or(usize::MAX), args.trace_nodes);
+        let mut out = BufWriter::new(File::create(path)?);
+        trace.write_dot(&mut out)?;
+        out.flush()?;
+    }
+    if let Some(output) = &mut output {
+        // The footer marks a finished run, so the file of a stopped run cannot be merged
+        if flow.is_continue() {
+            SolutionFile::write_footer(output, solutions, &solver.stats)?;
+        }
+        output.flush()?;
+    }
+
+    // Print Information
+    let duration = Instant::now().duration_since(start_time);
+    let s_per_solution = duration.as_secs_f64() / solutions as f64;
+    println!("\n===== Statistics =====");
+    println!("Total Solutions: {}", solutions);
+    println!("Total Duration: {}s", duration.as_secs());
+    println!("Rate: {:.2}ms per solution", s_per_solution * 1000.0);
+    println!("Nodes: {}", solver.stats.nodes);
+    if let Some(nodes) = solver.stats.nodes_to_first_solution {
+        println!("Nodes To First Solution: {}", nodes);
+    }
+    println!("Pruned Isolated Regions: {}", solver.stats.isolated_regions);
+    if let Some(format) = args.report {
+        let report = Report {
+            stats: &solver.stats,
+            durations: &solver.durations,
+        };
+        let mut out: Box<dyn Write> = match &args.report_file {
+            Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+            None => Box::new(io::stdout().lock()),
+        };
+        match format {
+            ReportFormat::Table => {
+                if args.report_file.is_none() {
+                    writeln!(out)?;
+                }
+                report.write_table(&mut out)?;
+            }
+            ReportFormat::Json => report.write_json(&mut out)?,
+        }
+        out.flush()?;
+    }
+    if flow.is_break() {
+        let reason = match max_solutions {
+            Some(max) if solutions >= max => "solution limit reached",
+            _ => "time limit reached",
+        };
+        println!("Stopped Early: {}", reason);
+    }
     Ok(())
 }
+
+/// Formats a duration in seconds with the largest unit that keeps it at least 1
+fn format_seconds(seconds: f64) -> String {
+    match seconds {
+        s if s < 1.0 => format!("{:.0}ms", s * 1000.0),
+        s if s < 60.0 => format!("{:.1}s", s),
+        s if s < 3600.0 => format!("{:.1}m", s / 60.0),
+        s if s < 86400.0 => format!("{:.1}h", s / 3600.0),
+        s => format!("{:.1}d", s / 86400.0),
+    }
+}
+
+fn estimate<const W: usize>(
+    args: &PuzzleArgs,
+    target: &Target,
+    backtracker: &Backtracker,
+    probes: usize,
+    seed: u64,
+) -> io::Result<()> {
+    let puzzle = args.load::<W>(target)?;
+    let estimate = Solver::build().estimate(&puzzle, backtracker, probes, seed);
+
+    let (fastest, slowest) = estimate.seconds.interval();
+    println!("\n===== Estimate =====");
+    println!("Probes: {}", estimate.probes);
+    for (name, mean) in [("Nodes", estimate.nodes), ("Solutions", estimate.solutions)] {
+        let (low, high) = mean.interval();
+        println!("{}: {:.0} (95% interval {:.0} to {:.0})", name, mean.mean, low, high);
+    }
+    println!(
+        "Duration: {} on one thread (95% interval {} to {})",
+        format_seconds(estimate.seconds.mean),
+        format_seconds(fastest),
+        format_seconds(slowest)
+    );
+    Ok(())
+}
+
+fn sample<const W: usize>(
+    args: &PuzzleArgs,
+    target: &Target,
+    count: usize,
+    seed: u64,
+    unique: bool,
+    mirror_equivalent: bool,
+) -> io::Result<()> {
+    let puzzle = args.load::<W>(target)?;
+    let strategy = Algorithm::default().strategy::<W>();
+
+    // The search finds at least one solution of each class of solutions related by a rotation,
+    // and some classes more than once, so every class is offered once, or every solution of it
+    // when drawing from all the solutions. The search passes on its solutions in the same order
+    // for any number of threads, so the sample only depends on the seed.
+    let mut reservoir = Reservoir::new(count, seed);
+    let mut classes = HashSet::new();
+    let _ = Solver::build().begin(&puzzle, strategy.as_ref(), &mut |solution| {
+        if classes.insert(solution.canonical(&puzzle, mirror_equivalent)) {
+            if unique || mirror_equivalent {
+                reservoir.offer(solution);
+            } else {
+                for image in solution.rotations(&puzzle) {
+                    reservoir.offer(&image);
+                }
+            }
+        }
+        ControlFlow::Continue(())
+    });
+
+    let solutions = reservoir.seen();
+    for solution in reservoir.into_items() {
+        puzzle.show(&solution);
+        println!();
+    }
+    println!("\n===== Sample =====");
+    let drawn_from = if unique || mirror_equivalent { "classes of solutions" } else { "solutions" };
+    println!("Sampled {} of {} {} with seed {}", count.min(solutions), solutions, drawn_from, seed);
+    Ok(())
+}
+
+fn merge(files: &[PathBuf], output: &Option<PathBuf>) -> io::Result<()> {
+    let files = files
+        .iter()
+        .map(|path| SolutionFile::read(path.clone()))
+        .collect::<io::Result<Vec<_>>>()?;
+    let merged = SolutionFile::merge(files)?;
+    if let Some(path) = output {
+        let mut output = BufWriter::new(File::create(path)?);
+        merged.write(&mut output)?;
+        output.flush()?;
+    }
+
+    println!("{}", merged.puzzle);
+    println!("\n===== Statistics =====");
+    println!("Total Solutions: {}", merged.solutions.len());
+    println!("Nodes: {}", merged.stats.nodes);
+    println!("Pruned Isolated Regions: {}", merged.stats.isolated_regions);
+    Ok(())
+}
+
+/// Calls `$solve::<W>` with the narrowest board representation that fits a box of dimensions
+/// `$dim`
+macro_rules! with_board_words {
+    ($dim:expr, $solve:ident($($arg:expr),*)) => {{
+        let dim: Coord = $dim;
+        match puzzle::words_for(dim.volume()) {
+            1 => $solve::<1>($($arg),*),
+            2 => $solve::<2>($($arg),*),
+            3..=4 => $solve::<4>($($arg),*),
+            5..=8 => $solve::<8>($($arg),*),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{}x{}x{} box has {} cells, at most {} are supported",
+                    dim.x,
+                    dim.y,
+                    dim.z,
+                    dim.volume(),
+                    puzzle::MAX_CELLS
+                ),
+            )),
+        }
+    }};
+}
+
+fn main() -> io::Result<()> {
+    let cli = Cli::parse();
+    match &cli.command {
+        Some(Command::Merge { files, output }) => return merge(files, output),
+        Some(Command::Estimate {
+            puzzle,
+            cell_order,
+            no_region_pruning,
+            probes,
+            seed,
+        }) => {
+            let target = puzzle.target()?;
+            let backtracker = Backtracker {
+                cell_order: *cell_order,
+                prune_isolated: !no_region_pruning,
+                ..Backtracker::default()
+            };
+            return with_board_words!(target.dim, estimate(puzzle, &target, &backtracker, *probes as usize, *seed));
+        }
+        Some(Command::Sample {
+            puzzle,
+            count,
+            seed,
+            unique,
+            mirror_equivalent,
+        }) => {
+            let target = puzzle.target()?;
+            return with_board_words!(
+                target.dim,
+                sample(puzzle, &target, *count, *seed, *unique, *mirror_equivalent)
+            );
+        }
+        None => {}
+    }
+
+    let puzzle = cli.puzzle.expect("clap requires the puzzle without a subcommand");
+    let args = cli.args;
+    if args.trace_dot.is_some() && args.algorithm == Algorithm::Dlx {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "--trace-dot draws the search of the bitset backtracker, not --algorithm dlx",
+        ));
+    }
+    if args.report.is_some() && args.algorithm == Algorithm::Dlx {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "--report counts the checks of the bitset backtracker, not --algorithm dlx",
+        ));
+    }
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(args.threads)
+        .build_global()
+        .map_err(io::Error::other)?;
+
+    let target = puzzle.target()?;
+    with_board_words!(target.dim, solve(&puzzle, &args, &target))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oversized_boxes_are_rejected() {
+        let args = |size: &str| PuzzleArgs {
+            puzzle: PathBuf::from("puzzles/soma-cube.csv"),
+            size: size.to_string(),
+            target: None,
+            reflected_pieces: false,
+        };
+        assert_eq!(args("8x8x8").target().unwrap().cells.len(), 512);
+        for size in ["8x8x9", "2000x2000x2000", "99999999x99999999x99999999"] {
+            assert!(args(size).target().is_err(), "{size}");
+        }
+    }
+
+    #[test]
+    fn fnv1a_hashes() {
+        assert_eq!(fnv1a(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(fnv1a(b"foobar"), 0x85944171f73967e8);
+    }
+
+    #[test]
+    fn parse_durations() {
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("5m"), Ok(Duration::from_secs(300)));
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(5400)));
+        assert_eq!(parse_duration(" 1.5d "), Ok(Duration::from_secs(129600)));
+        for invalid in ["", "s", "5x", "1h-30m", "m5"] {
+            assert!(parse_duration(invalid).is_err(), "{invalid:?}");
+        }
+    }
+}
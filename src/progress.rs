@@ -0,0 +1,58 @@
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+/// Counters of a running search, updated by its workers and read from another thread to show
+/// how far the search has got
+#[derive(Debug, Default)]
+pub struct Progress {
+    /// Starting arrangement being searched, from 0, or work unit when searching a shard
+    pub start: AtomicUsize,
+    /// Number of starting arrangements, or of work units in the shard
+    pub starts: AtomicUsize,
+    /// Top-level branches of the current starting arrangement searched so far
+    pub branches_done: AtomicUsize,
+    /// Number of top-level branches of the current starting arrangement, 0 until known
+    pub branches: AtomicUsize,
+    /// Search nodes of the finished branches
+    pub nodes: AtomicU64,
+    /// Solutions passed on so far
+    pub solutions: AtomicU64,
+}
+
+impl Progress {
+    pub fn begin_start(&self, start: usize, starts: usize) {
+        self.branches.store(0, Ordering::Relaxed);
+        self.branches_done.store(0, Ordering::Relaxed);
+        self.start.store(start, Ordering::Relaxed);
+        self.starts.store(starts, Ordering::Relaxed);
+    }
+
+    pub fn begin_branches(&self, branches: usize) {
+        self.branches_done.store(0, Ordering::Relaxed);
+        self.branches.store(branches, Ordering::Relaxed);
+    }
+
+    /// Records a finished top-level branch and the nodes it visited
+    pub fn finish_branch(&self, nodes: u64) {
+        self.add_nodes(nodes);
+        self.branches_done.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn add_nodes(&self, nodes: u64) {
+        self.nodes.fetch_add(nodes, Ordering::Relaxed);
+    }
+
+    /// Fraction of the search finished, counting every starting arrangement, and every branch
+    /// of one, as the same amount of work
+    pub fn fraction(&self) -> f64 {
+        let starts = self.starts.load(Ordering::Relaxed);
+        if starts == 0 {
+            return 0.0;
+        }
+        let branches = self.branches.load(Ordering::Relaxed);
+        let within = match branches {
+            0 => 0.0,
+            _ => self.branches_done.load(Ordering::Relaxed) as f64 / branches as f64,
+        };
+        ((self.start.load(Ordering::Relaxed) as f64 + within.min(1.0)) / starts as f64).min(1.0)
+    }
+}
@@ -1,30 +1,51 @@
+use crate::solution::Solution;
+use crate::symmetry::{CellMap, Symmetry};
+use crate::target::Target;
+
 use colored::*;
 use itertools::Itertools;
 use std::fs::File;
 use std::hash::{Hash, Hasher};
 use std::path::PathBuf;
 use std::simd::u64x8;
+use std::str::FromStr;
 use std::{fmt, io};
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
-pub struct Bitset(pub u64);
+/// Set of board cells, stored in `W` 64-bit words so that boards larger than 64 cells can be
+/// represented. Single word boards (the default) keep the 64-bit fast path.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Bitset<const W: usize = 1>(pub [u64; W]);
+
+pub type Board<const W: usize = 1> = Bitset<W>;
+pub type Placement<const W: usize = 1> = Bitset<W>;
+
+/// Number of 64-bit words needed for a board with `cells` cells
+pub fn words_for(cells: usize) -> usize {
+    cells.div_ceil(64)
+}
 
-pub type Board = Bitset;
-pub type Placement = Bitset;
+/// Most cells a box can have, in the widest board representation
+pub const MAX_CELLS: usize = Bitset::<8>::CAPACITY;
 
-impl fmt::Debug for Bitset {
+impl<const W: usize> fmt::Debug for Bitset<W> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.0)
+        write!(f, "{}", self.0.iter().join(":"))
     }
 }
-impl fmt::Display for Bitset {
+
+/// Formats a bitset as layers of a box with the given dimensions
+pub struct BitsetDisplay<const W: usize> {
+    bitset: Bitset<W>,
+    dim: Coord,
+}
+
+impl<const W: usize> fmt::Display for BitsetDisplay<W> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        // Use self.bitmask() to get the bitmask and format it
-        for y in 0..4 {
-            for z in 0..4 {
-                for x in 0..4 {
-                    let c = Coord { x, y, z }.to_index();
-                    if self.get(c) {
+        for y in 0..self.dim.y {
+            for z in 0..self.dim.z {
+                for x in 0..self.dim.x {
+                    let c = Coord { x, y, z }.to_index(self.dim);
+                    if self.bitset.get(c) {
                         write!(f, "X")?;
                     } else {
                         write!(f, ".")?;
@@ -32,85 +53,217 @@ impl fmt::Display for Bitset {
                 }
                 write!(f, " ")?;
             }
-            writeln!(f, "")?;
+            writeln!(f)?;
         }
         Ok(())
     }
 }
 
-impl Bitset {
-    pub const MAX: u64 = u64::MAX;
-    pub const DIMENSION: usize = 4;
+impl<const W: usize> Default for Bitset<W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const W: usize> Bitset<W> {
+    /// Largest number of cells the bitset can hold
+    pub const CAPACITY: usize = 64 * W;
+
+    pub fn new() -> Bitset<W> {
+        Bitset([0; W])
+    }
+
+    /// Bitset with the lowest `cells` bits set
+    pub fn full(cells: usize) -> Bitset<W> {
+        let mut bitset = Bitset::new();
+        for (w, word) in bitset.0.iter_mut().enumerate() {
+            let bits = cells.saturating_sub(w * 64);
+            *word = if bits >= 64 { u64::MAX } else { (1 << bits) - 1 };
+        }
+        bitset
+    }
 
-    pub fn new() -> Bitset {
-        Bitset(0)
+    /// Bitset with only the bit at `index` set
+    pub fn single(index: usize) -> Bitset<W> {
+        let mut bitset = Bitset::new();
+        bitset.set(index);
+        bitset
     }
 
-    pub fn from_orientation(orientation: &Orientation) -> Bitset {
-        let mut mask = Bitset(0);
+    pub fn from_orientation(orientation: &Orientation, dim: Coord) -> Bitset<W> {
+        let mut mask = Bitset::new();
         for coord in &orientation.0 {
-            mask.0 |= 1 << ((coord.z as u64) * 16 + (coord.y as u64) * 4 + (coord.x as u64))
+            mask.set(coord.to_index(dim));
         }
         mask
     }
-    pub fn has_coord_set(&self, coord: &Coord) -> bool {
-        (((self.0 >> Self::DIMENSION * Self::DIMENSION * coord.z as usize)
-            >> Self::DIMENSION * coord.y as usize)
-            >> coord.x as usize)
-            & 1
-            == 1
+
+    pub fn has_coord_set(&self, coord: &Coord, dim: Coord) -> bool {
+        self.get(coord.to_index(dim))
+    }
+
+    pub fn display(self, dim: Coord) -> BitsetDisplay<W> {
+        BitsetDisplay { bitset: self, dim }
     }
 
     pub fn get(&self, index: usize) -> bool {
-        (self.0 >> index) & 1 == 1
+        (self.0[index / 64] >> (index % 64)) & 1 == 1
     }
 
     pub fn set(&mut self, index: usize) {
-        self.0 |= 1 << index;
+        self.0[index / 64] |= 1 << (index % 64);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|&word| word == 0)
+    }
+
+    pub fn intersects(&self, other: Bitset<W>) -> bool {
+        self.0.iter().zip(other.0).any(|(a, b)| a & b != 0)
+    }
+
+    pub fn xor(&self, other: Bitset<W>) -> Bitset<W> {
+        Bitset(std::array::from_fn(|w| self.0[w] ^ other.0[w]))
+    }
+
+    pub fn union(&self, other: Bitset<W>) -> Bitset<W> {
+        Bitset(std::array::from_fn(|w| self.0[w] | other.0[w]))
     }
 
-    pub fn intersects(&self, other: Bitset) -> bool {
-        (self.0 & other.0) != 0
+    pub fn intersection(&self, other: Bitset<W>) -> Bitset<W> {
+        Bitset(std::array::from_fn(|w| self.0[w] & other.0[w]))
     }
 
-    pub fn xor(&self, other: Bitset) -> Bitset {
-        Bitset(self.0 ^ other.0)
+    /// Bits set in `self` but not in `other`
+    pub fn difference(&self, other: Bitset<W>) -> Bitset<W> {
+        Bitset(std::array::from_fn(|w| self.0[w] & !other.0[w]))
     }
 
-    pub fn union(&self, other: Bitset) -> Bitset {
-        Bitset(self.0 | other.0)
+    pub fn count(&self) -> usize {
+        self.0.iter().map(|word| word.count_ones() as usize).sum()
     }
 
-    pub fn intersection(&self, other: Bitset) -> Bitset {
-        Bitset(self.0 & other.0)
+    /// Index of the lowest set bit
+    pub fn first(&self) -> Option<usize> {
+        self.0
+            .iter()
+            .position(|&word| word != 0)
+            .map(|w| w * 64 + self.0[w].trailing_zeros() as usize)
+    }
+
+    /// Every index moved up by `n`, dropping those that pass the capacity
+    pub fn shifted_up(&self, n: usize) -> Bitset<W> {
+        let (words, bits) = (n / 64, n % 64);
+        let mut shifted = Bitset::new();
+        for w in words..W {
+            shifted.0[w] = self.0[w - words] << bits;
+            if bits > 0 && w > words {
+                shifted.0[w] |= self.0[w - words - 1] >> (64 - bits);
+            }
+        }
+        shifted
+    }
+
+    /// Indices of the set bits, lowest first
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0.iter().enumerate().flat_map(|(w, &word)| {
+            let mut word = word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(w * 64 + bit)
+            })
+        })
+    }
+}
+
+/// Most pieces a puzzle can have, so that any set of them fits in a `PieceSet`
+pub const MAX_PIECES: usize = 64;
+
+/// Set of piece indices, one bit per piece
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PieceSet(pub u64);
+
+impl PieceSet {
+    /// Set of the pieces `0..count`
+    pub fn first(count: usize) -> PieceSet {
+        PieceSet(if count == MAX_PIECES { u64::MAX } else { (1 << count) - 1 })
+    }
+
+    pub fn contains(&self, piece: usize) -> bool {
+        self.0 & (1 << piece) != 0
+    }
+
+    /// The set without `piece`
+    pub fn without(&self, piece: usize) -> PieceSet {
+        PieceSet(self.0 & !(1 << piece))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Pieces in the set, lowest index first
+    pub fn iter(&self) -> impl Iterator<Item = usize> {
+        let mut bits = self.0;
+        std::iter::from_fn(move || {
+            if bits == 0 {
+                return None;
+            }
+            let piece = bits.trailing_zeros() as usize;
+            bits &= bits - 1;
+            Some(piece)
+        })
+    }
+}
+
+impl FromIterator<usize> for PieceSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(pieces: I) -> PieceSet {
+        PieceSet(pieces.into_iter().fold(0, |bits, piece| bits | (1 << piece)))
     }
 }
 
 #[derive(Clone)]
-pub struct Piece {
+pub struct Piece<const W: usize = 1> {
     pub name: String,
     pub code: String,
     pub base: Orientation,
-    pub placements: Vec<Placement>,
-    simd_placements: Vec<u64x8>,
+    pub placements: Vec<Placement<W>>,
+    /// Placements in chunks of 8, transposed so that lane `i` of word `w` holds word `w` of the
+    /// `i`th placement in the chunk
+    simd_placements: Vec<[u64x8; W]>,
 }
 
-impl PartialEq for Piece {
+impl<const W: usize> PartialEq for Piece<W> {
     fn eq(&self, other: &Self) -> bool {
         // Equality based on the bitmask
         self.name == other.name
     }
 }
 
-impl Eq for Piece {}
+impl<const W: usize> Eq for Piece<W> {}
 
-impl fmt::Debug for Piece {
+impl<const W: usize> fmt::Debug for Piece<W> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", format!("Piece {{ {: ^20} }}", self.name))
+        write!(f, "Piece {{ {: ^20} }}", self.name)
     }
 }
-impl Piece {
-    fn new(name: String, code: String, base: Orientation, dim: Coord) -> Piece {
+impl<const W: usize> Piece<W> {
+    fn new(
+        name: String,
+        code: String,
+        base: Orientation,
+        dim: Coord,
+        blocked: Board<W>,
+        reflections: bool,
+    ) -> Piece<W> {
         let mut piece = Piece {
             name,
             code,
@@ -118,46 +271,76 @@ impl Piece {
             placements: vec![],
             simd_placements: vec![],
         };
-        let orientations = piece.generate_unique_orientations(dim);
-        piece.compute_possible_positions(&orientations);
+        let orientations = piece.generate_unique_orientations(reflections);
+        piece.compute_possible_positions(&orientations, dim, blocked);
 
         piece.generate_simd_placements();
 
         piece
     }
 
-    pub fn placements(&self) -> &Vec<Placement> {
+    /// Number of cubes in the piece
+    pub fn volume(&self) -> usize {
+        self.base.0.len()
+    }
+
+    pub fn placements(&self) -> &Vec<Placement<W>> {
         &self.placements
     }
 
-    pub fn simd_placements(&self) -> &Vec<u64x8> {
+    pub fn simd_placements(&self) -> &Vec<[u64x8; W]> {
         &self.simd_placements
     }
 
-    fn generate_unique_orientations(&mut self, dim: Coord) -> Vec<Orientation> {
-        let mut orientations = self.base.get_all_rotations(dim);
+    /// Distinct orientations of the piece, including those of its mirror image if `reflections`
+    /// is set
+    fn generate_unique_orientations(&self, reflections: bool) -> Vec<Orientation> {
+        let mut bases = vec![self.base.clone()];
+        if reflections {
+            bases.push(self.base.mirrored());
+        }
+        let mut orientations = vec![];
+        for mut base in bases {
+            // Rotate within the piece's own bounding cube so that no rotation wraps it, whatever
+            // the shape of the puzzle box
+            base.normalise();
+            let side = base.0.iter().map(|c| c.x.max(c.y).max(c.z)).max().unwrap() + 1;
+            orientations.extend(base.get_all_rotations(Coord {
+                x: side,
+                y: side,
+                z: side,
+            }));
+        }
         orientations.iter_mut().for_each(|o| o.normalise());
 
         let unique_orientations: Vec<Orientation> =
-            orientations.iter().unique().map(|x| x.clone()).collect();
+            orientations.iter().unique().cloned().collect();
         unique_orientations
     }
 
-    fn compute_possible_positions(&mut self, unique_orientations: &Vec<Orientation>) {
+    fn compute_possible_positions(
+        &mut self,
+        unique_orientations: &[Orientation],
+        dim: Coord,
+        blocked: Board<W>,
+    ) {
         for orientation in unique_orientations {
             let x_bound = orientation.0.iter().map(|coord| coord.x).max().unwrap();
             let y_bound = orientation.0.iter().map(|coord| coord.y).max().unwrap();
             let z_bound = orientation.0.iter().map(|coord| coord.z).max().unwrap();
-            for x_off in 0..(4 - x_bound) {
-                for y_off in 0..(4 - y_bound) {
-                    for z_off in 0..(4 - z_bound) {
+            for x_off in 0..(dim.x - x_bound) {
+                for y_off in 0..(dim.y - y_bound) {
+                    for z_off in 0..(dim.z - z_bound) {
                         let mut new_pos = orientation.clone();
                         new_pos.0.iter_mut().for_each(|coord| {
                             coord.x += x_off;
                             coord.y += y_off;
                             coord.z += z_off;
                         });
-                        self.placements.push(Placement::from_orientation(&new_pos));
+                        let placement = Placement::from_orientation(&new_pos, dim);
+                        if !placement.intersects(blocked) {
+                            self.placements.push(placement);
+                        }
                     }
                 }
             }
@@ -167,11 +350,13 @@ impl Piece {
     fn generate_simd_placements(&mut self) {
         let mut simd_placements = vec![];
         for placement in self.placements.chunks(8) {
-            let mut placement_array = [0; 8];
+            let mut placement_arrays = [[0; 8]; W];
             for (i, p) in placement.iter().enumerate() {
-                placement_array[i] = p.0;
+                for (w, word) in p.0.iter().enumerate() {
+                    placement_arrays[w][i] = *word;
+                }
             }
-            simd_placements.push(u64x8::from_array(placement_array));
+            simd_placements.push(placement_arrays.map(u64x8::from_array));
         }
         self.simd_placements = simd_placements;
     }
@@ -182,31 +367,28 @@ pub struct Orientation(Vec<Coord>);
 
 impl Hash for Orientation {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        // Get the bitmask and feed it into the hasher
-        let placement = Placement::from_orientation(self);
-        placement.0.hash(state);
+        // Hash the set of cells, independent of the order they were listed in
+        self.sorted_coords().hash(state);
     }
 }
 
 impl PartialEq for Orientation {
     fn eq(&self, other: &Self) -> bool {
-        // Equality based on the bitmask
-        let placement_a = Placement::from_orientation(self);
-        let placement_b = Placement::from_orientation(other);
-        placement_a.0 == placement_b.0
+        // Equality based on the set of cells
+        self.sorted_coords() == other.sorted_coords()
     }
 }
 
 impl Eq for Orientation {}
 
 impl Orientation {
-    pub fn from_placement(placement: Placement) -> Orientation {
+    pub fn from_placement<const W: usize>(placement: Placement<W>, dim: Coord) -> Orientation {
         let mut coords = Vec::new();
-        for y in 0..4 {
-            for z in 0..4 {
-                for x in 0..4 {
+        for y in 0..dim.y {
+            for z in 0..dim.z {
+                for x in 0..dim.x {
                     let c = Coord { x, y, z };
-                    if placement.has_coord_set(&c) {
+                    if placement.has_coord_set(&c, dim) {
                         coords.push(c);
                     }
                 }
@@ -215,6 +397,24 @@ impl Orientation {
         Orientation(coords)
     }
 
+    /// Mirror image of the orientation, reflected in the x axis
+    pub fn mirrored(&self) -> Orientation {
+        let mut mirrored = Orientation(
+            self.0
+                .iter()
+                .map(|c| Coord { x: -c.x, ..*c })
+                .collect(),
+        );
+        mirrored.normalise();
+        mirrored
+    }
+
+    fn sorted_coords(&self) -> Vec<Coord> {
+        let mut coords = self.0.clone();
+        coords.sort_by_key(|c| (c.z, c.y, c.x));
+        coords
+    }
+
     fn rotate_within(&mut self, x: usize, y: usize, z: usize, dim: Coord) {
         // Rotate
         for _ in 0..x {
@@ -292,15 +492,9 @@ impl Orientation {
         let min_y = self.0.iter().map(|coord| coord.y).min().unwrap();
         let min_z = self.0.iter().map(|coord| coord.z).min().unwrap();
 
-        self.0
-            .iter_mut()
-            .for_each(|coord| coord.x = coord.x - min_x);
-        self.0
-            .iter_mut()
-            .for_each(|coord| coord.y = coord.y - min_y);
-        self.0
-            .iter_mut()
-            .for_each(|coord| coord.z = coord.z - min_z);
+        self.0.iter_mut().for_each(|coord| coord.x -= min_x);
+        self.0.iter_mut().for_each(|coord| coord.y -= min_y);
+        self.0.iter_mut().for_each(|coord| coord.z -= min_z);
     }
 
     pub fn normalise_to_board(&mut self, dimension: i64) {
@@ -323,6 +517,22 @@ pub struct Coord {
     pub z: i64,
 }
 
+impl FromStr for Coord {
+    type Err = String;
+
+    /// Parses box dimensions written as `XxYxZ`, e.g. `2x4x8`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let sizes: Vec<i64> = s
+            .split('x')
+            .map(|d| d.trim().parse().map_err(|_| format!("invalid size {s:?}")))
+            .collect::<Result<_, _>>()?;
+        match sizes[..] {
+            [x, y, z] if x > 0 && y > 0 && z > 0 => Ok(Coord { x, y, z }),
+            _ => Err(format!("invalid size {s:?}, expected XxYxZ")),
+        }
+    }
+}
+
 impl Coord {
     pub fn new(x: usize, y: usize, z: usize) -> Coord {
         Coord {
@@ -332,17 +542,19 @@ impl Coord {
         }
     }
 
-    pub fn from_str(s: &str) -> Coord {
-        let mut coord_ids = s.split('x');
-        Coord {
-            x: coord_ids.next().unwrap().parse().unwrap(),
-            y: coord_ids.next().unwrap().parse().unwrap(),
-            z: coord_ids.next().unwrap().parse().unwrap(),
-        }
+    /// Number of cells in a box with these dimensions
+    pub fn volume(&self) -> usize {
+        (self.x * self.y * self.z) as usize
+    }
+
+    /// Number of cells in a box with these dimensions, or none if it does not fit in a `usize`
+    pub fn checked_volume(&self) -> Option<usize> {
+        let cells = self.x.checked_mul(self.y)?.checked_mul(self.z)?;
+        usize::try_from(cells).ok()
     }
 
-    pub fn to_index(&self) -> usize {
-        (self.z * 16 + self.y * 4 + self.x) as usize
+    pub fn to_index(&self, dim: Coord) -> usize {
+        (self.z * dim.y * dim.x + self.y * dim.x + self.x) as usize
     }
 
     pub fn from_index(index: usize, dim: Coord) -> Coord {
@@ -354,7 +566,7 @@ impl Coord {
     }
 
     fn from_str_list(s: &str) -> Vec<Coord> {
-        s.split("-")
+        s.split('-')
             .map(|coord_s| {
                 let mut coord_ids = coord_s.chars();
                 Coord {
@@ -406,17 +618,52 @@ impl Coord {
     }
 }
 
-pub struct Puzzle {
+pub struct Puzzle<const W: usize = 1> {
     pub name: String,
-    pub pieces: Vec<Piece>,
-    pub lookup: Vec<Vec<(usize, Placement)>>,
+    pub pieces: Vec<Piece<W>>,
+    /// Placements covering each cell, bucketed by piece: `lookup[cell][piece]`
+    pub lookup: Vec<Vec<Vec<Placement<W>>>>,
+    /// Cells sharing a face with each cell
+    pub neighbours: Vec<Board<W>>,
     pub dim: Coord,
+    /// Every cell of the box set
+    pub full: Board<W>,
+    /// Cells of the box the pieces have to fill
+    pub target: Board<W>,
+    /// Cell maps of the symmetries that map the box and the target onto themselves: the
+    /// rotations, starting with the identity, then the reflections
+    pub symmetries: Vec<CellMap>,
+    /// Piece each piece turns into when the puzzle is reflected. Pieces without a mirror image
+    /// in the set map to themselves.
+    pub mirrors: Vec<usize>,
 }
 
-impl Puzzle {
-    pub fn from_csv(path: PathBuf, size: &str) -> io::Result<Self> {
+impl<const W: usize> Puzzle<W> {
+    /// Loads the pieces listed in a CSV file. With `reflections` set, chiral pieces may also be
+    /// placed as their mirror image.
+    pub fn from_csv(path: PathBuf, target: &Target, reflections: bool) -> io::Result<Self> {
+        let dim = target.dim;
+        if dim.volume() > Board::<W>::CAPACITY {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{}x{}x{} box has {} cells, at most {} are supported",
+                    dim.x,
+                    dim.y,
+                    dim.z,
+                    dim.volume(),
+                    Board::<W>::CAPACITY
+                ),
+            ));
+        }
+        let full = Board::full(dim.volume());
+        let mut target_board = Board::new();
+        for coord in &target.cells {
+            target_board.set(coord.to_index(dim));
+        }
+        let blocked = full.xor(target_board);
+
         let file = File::open(path)?;
-        let dim = Coord::from_str(size);
         let mut rdr = csv::Reader::from_reader(file);
         let mut pieces = vec![];
         for (idx, result) in rdr.records().enumerate() {
@@ -427,30 +674,115 @@ impl Puzzle {
                 format!("{:x}", idx).to_uppercase().color(color).to_string(),
                 Orientation(Coord::from_str_list(&record[2])),
                 dim,
+                blocked,
+                reflections,
             ));
         }
 
-        let mut lookup = vec![Vec::new(); 64];
+        let shapes: Vec<Vec<Orientation>> = pieces
+            .iter()
+            .map(|piece| piece.generate_unique_orientations(reflections))
+            .collect();
+        let mirrors = (0..pieces.len())
+            .map(|idx| {
+                let mirrored = pieces[idx].base.mirrored();
+                std::iter::once(idx)
+                    .chain(0..pieces.len())
+                    .find(|&other| shapes[other].contains(&mirrored))
+                    .unwrap_or(idx)
+            })
+            .collect();
+
+        if pieces.len() > MAX_PIECES {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} pieces, at most {} are supported", pieces.len(), MAX_PIECES),
+            ));
+        }
+
+        let mut lookup = vec![vec![Vec::new(); pieces.len()]; dim.volume()];
 
         for (idx, piece) in pieces.iter().enumerate() {
             for placement in piece.placements() {
-                // lookup[idx] = lookup[idx].union(*placement);
-                for coord in Orientation::from_placement(*placement).0 {
-                    lookup[coord.to_index()].push((idx, *placement));
+                for cell in placement.ones() {
+                    lookup[cell][idx].push(*placement);
                 }
             }
         }
 
+        let neighbours = (0..dim.volume())
+            .map(|index| {
+                let c = Coord::from_index(index, dim);
+                let mut board = Board::new();
+                for (dx, dy, dz) in [(1, 0, 0), (0, 1, 0), (0, 0, 1)] {
+                    for sign in [-1, 1] {
+                        let n = Coord {
+                            x: c.x + sign * dx,
+                            y: c.y + sign * dy,
+                            z: c.z + sign * dz,
+                        };
+                        if (0..dim.x).contains(&n.x)
+                            && (0..dim.y).contains(&n.y)
+                            && (0..dim.z).contains(&n.z)
+                        {
+                            board.set(n.to_index(dim));
+                        }
+                    }
+                }
+                board
+            })
+            .collect();
+
+        // Only the symmetries of the box that also map the target onto itself relate solutions
+        let symmetries = Symmetry::all()
+            .iter()
+            .filter_map(|symmetry| symmetry.cell_map(dim))
+            .filter(|map| map.apply(target_board) == target_board)
+            .collect();
+
         Ok(Puzzle {
             name: "Bedlam Cube".to_string(),
             pieces,
             lookup,
+            neighbours,
             dim,
+            full,
+            target: target_board,
+            symmetries,
+            mirrors,
         })
     }
 
+    /// Cells of the box outside the target, which are treated as already occupied
+    pub fn blocked(&self) -> Board<W> {
+        self.full.xor(self.target)
+    }
+
+    /// Connected region of `cells` containing the cell `seed`
+    pub fn region(&self, cells: Board<W>, seed: usize) -> Board<W> {
+        let mut region = Board::single(seed);
+        let mut frontier = region;
+        while !frontier.is_empty() {
+            let mut reached = Board::new();
+            for cell in frontier.ones() {
+                reached = reached.union(self.neighbours[cell]);
+            }
+            frontier = reached.intersection(cells).difference(region);
+            region = region.union(frontier);
+        }
+        region
+    }
+
+    /// Arrangement with no pieces placed yet
+    pub fn arrangement(&self) -> Arrangement<W> {
+        Arrangement {
+            occupied: self.blocked(),
+            placements: vec![],
+        }
+    }
+
     pub fn corners(&self) -> Vec<Coord> {
-        vec![
+        [
             Coord::new(0, 0, 0),
             Coord::new(self.dim.x as usize - 1, 0, 0),
             Coord::new(0, self.dim.y as usize - 1, 0),
@@ -464,28 +796,22 @@ impl Puzzle {
                 self.dim.z as usize - 1,
             ),
         ]
-        .iter()
+        .into_iter()
         .rev()
-        .cloned()
         .collect()
     }
 
-    pub fn show(&self, arrangements: &[&Arrangement]) {
+    pub fn show(&self, solution: &Solution<W>) {
         for y in (0..self.dim.y).rev() {
             for z in 0..self.dim.z {
                 for x in 0..self.dim.x {
-                    let index = z * self.dim.y * self.dim.x + y * self.dim.x + x;
-                    for a in arrangements {
-                        if a.occupied.get(index as usize) {
-                            for (id, bits) in a.placements.iter() {
-                                if bits.get(index as usize) {
-                                    // print!("{} ", self.pieces[*id].colored_id());
-                                    print!("{} ", self.pieces[*id].code);
-                                    break;
-                                }
-                            }
-                        }
-                        
+                    let index = Coord { x, y, z }.to_index(self.dim);
+                    let piece = solution.placements.iter().find(|(_, bits)| bits.get(index));
+                    match piece {
+                        // print!("{} ", self.pieces[*id].colored_id());
+                        Some((id, _)) => print!("{} ", self.pieces[*id].code),
+                        // Cells outside the target
+                        None => print!("  "),
                     }
                 }
                 print!("  ");
@@ -495,32 +821,28 @@ impl Puzzle {
     }
 }
 
-#[derive(Clone)]
-pub struct Arrangement {
-    pub occupied: Board,
-    pub placements: Vec<(usize, Placement)>,
+#[derive(Clone, Default)]
+pub struct Arrangement<const W: usize = 1> {
+    pub occupied: Board<W>,
+    pub placements: Vec<(usize, Placement<W>)>,
 }
 
-impl Arrangement {
-    pub fn new() -> Arrangement {
+impl<const W: usize> Arrangement<W> {
+    pub fn new() -> Arrangement<W> {
         Arrangement {
             occupied: Board::new(),
             placements: vec![],
         }
     }
 
-    pub fn push(&mut self, piece: usize, placement: Placement) {
+    pub fn push(&mut self, piece: usize, placement: Placement<W>) {
         self.occupied = self.occupied.union(placement);
         self.placements.push((piece, placement));
     }
 
-    pub fn pop(&mut self) -> Option<(usize, Placement)> {
-        match self.placements.pop() {
-            Some((piece, placement)) => {
-                self.occupied = self.occupied.xor(placement);
-                Some((piece, placement))
-            }
-            None => None,
-        }
+    pub fn pop(&mut self) -> Option<(usize, Placement<W>)> {
+        let (piece, placement) = self.placements.pop()?;
+        self.occupied = self.occupied.xor(placement);
+        Some((piece, placement))
     }
 }
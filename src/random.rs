@@ -0,0 +1,81 @@
+/// Small seeded pseudo-random number generator (SplitMix64), so that randomised searches can be
+/// repeated exactly from their seed
+#[derive(Clone, Debug)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Random {
+        Random { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniformly distributed integer below `n`, which must be positive
+    pub fn below(&mut self, n: usize) -> usize {
+        // Reject the values past the last whole multiple of `n` so that no result is favoured
+        let n = n as u64;
+        let zone = u64::MAX - u64::MAX % n;
+        loop {
+            let value = self.next_u64();
+            if value < zone {
+                return (value % n) as usize;
+            }
+        }
+    }
+}
+
+/// Uniform random sample of up to `capacity` items from a sequence of unknown length, in the
+/// order the items were offered (Vitter's Algorithm R)
+#[derive(Clone, Debug)]
+pub struct Reservoir<T> {
+    random: Random,
+    capacity: usize,
+    /// Number of items offered so far
+    seen: usize,
+    /// Sampled items, with their positions in the sequence
+    items: Vec<(usize, T)>,
+}
+
+impl<T: Clone> Reservoir<T> {
+    pub fn new(capacity: usize, seed: u64) -> Reservoir<T> {
+        Reservoir {
+            random: Random::new(seed),
+            capacity,
+            seen: 0,
+            items: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Offers the next item of the sequence, which replaces a sampled one with probability
+    /// `capacity / seen`
+    pub fn offer(&mut self, item: &T) {
+        let index = self.seen;
+        self.seen += 1;
+        if self.items.len() < self.capacity {
+            self.items.push((index, item.clone()));
+            return;
+        }
+        let slot = self.random.below(self.seen);
+        if slot < self.capacity {
+            self.items[slot] = (index, item.clone());
+        }
+    }
+
+    pub fn seen(&self) -> usize {
+        self.seen
+    }
+
+    /// The sampled items, in sequence order
+    pub fn into_items(mut self) -> Vec<T> {
+        self.items.sort_by_key(|&(index, _)| index);
+        self.items.into_iter().map(|(_, item)| item).collect()
+    }
+}
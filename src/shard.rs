@@ -0,0 +1,333 @@
+use crate::stats::Statistics;
+
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::iter::StepBy;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// One of `count` interleaved subsets of the solver's work units, numbered from 1
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Shard {
+    pub index: usize,
+    pub count: usize,
+}
+
+impl Shard {
+    /// The only shard of an unsplit run
+    pub const ALL: Shard = Shard { index: 1, count: 1 };
+
+    /// Indices of the work units, out of `units`, that belong to the shard: every `count`th one,
+    /// so that the costly units of the first starting arrangements are spread over the shards
+    pub fn units(&self, units: usize) -> StepBy<Range<usize>> {
+        (self.index - 1..units).step_by(self.count)
+    }
+
+    /// Index of the work unit at `position` in the shard
+    pub fn unit(&self, position: usize) -> usize {
+        self.index - 1 + position * self.count
+    }
+}
+
+impl FromStr for Shard {
+    type Err = String;
+
+    /// Parses a shard written as `i/n`, e.g. `2/8`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid shard {s:?}, expected i/n with 1 <= i <= n");
+        let (index, count) = s.split_once('/').ok_or_else(invalid)?;
+        let index: usize = index.trim().parse().map_err(|_| invalid())?;
+        let count: usize = count.trim().parse().map_err(|_| invalid())?;
+        if index == 0 || index > count {
+            return Err(invalid());
+        }
+        Ok(Shard { index, count })
+    }
+}
+
+impl fmt::Display for Shard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.index, self.count)
+    }
+}
+
+/// Solutions of one run, one per line (see `Solution::to_line`), with a description of the run,
+/// the work unit of the solutions that follow when it changes, and its counters in `#` comment
+/// lines. Checkpoints of an unfinished run use the same format,
+/// with a `# completed-units` line and, once a solution is found, a `# nodes-to-first-solution`
+/// line.
+///
+/// ```text
+/// # puzzle 3x3x3 pieces 795d37c1ce5216cb target 545e07db97361aa6
+/// # shard 2/2
+/// # unit 1
+/// 001124113...
+/// # solutions 1
+/// # nodes 6590
+/// # placements 31180
+/// # overlaps 16893
+/// # uncovered-cells 6322
+/// # unplaceable-pieces 1398
+/// # isolated-regions 5
+/// # depth-nodes 0 1 12 97 ...
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SolutionFile {
+    /// Puzzle and target the solutions are for
+    pub puzzle: String,
+    pub shard: Option<Shard>,
+    /// Solutions, each with the work unit it was found in, numbered across every shard, so that
+    /// `merge` can put them back in the order of an unsplit run
+    pub solutions: Vec<(usize, String)>,
+    pub stats: Statistics,
+    /// Number of work units of the shard searched so far, in a checkpoint of an unfinished run
+    pub completed_units: Option<usize>,
+}
+
+impl SolutionFile {
+    /// Writes the lines that come before the solutions
+    pub fn write_header(out: &mut impl Write, puzzle: &str, shard: Shard) -> io::Result<()> {
+        writeln!(out, "# puzzle {puzzle}")?;
+        writeln!(out, "# shard {shard}")
+    }
+
+    /// Writes the line giving the work unit of the solutions that follow, if it is not `last_unit`.
+    /// Solutions before any such line are from unit 0.
+    pub fn write_unit(out: &mut impl Write, last_unit: &mut usize, unit: usize) -> io::Result<()> {
+        if unit != *last_unit {
+            writeln!(out, "# unit {unit}")?;
+            *last_unit = unit;
+        }
+        Ok(())
+    }
+
+    /// Writes the lines that come after the solutions
+    pub fn write_footer(out: &mut impl Write, solutions: usize, stats: &Statistics) -> io::Result<()> {
+        let depths: Vec<String> = stats.depths().iter().map(u64::to_string).collect();
+        writeln!(out, "# solutions {solutions}")?;
+        writeln!(out, "# nodes {}", stats.nodes)?;
+        writeln!(out, "# placements {}", stats.placements)?;
+        writeln!(out, "# overlaps {}", stats.overlaps)?;
+        writeln!(out, "# uncovered-cells {}", stats.uncovered_cells)?;
+        writeln!(out, "# unplaceable-pieces {}", stats.unplaceable_pieces)?;
+        writeln!(out, "# isolated-regions {}", stats.isolated_regions)?;
+        writeln!(out, "# depth-nodes {}", depths.join(" "))
+    }
+
+    pub fn write(&self, out: &mut impl Write) -> io::Result<()> {
+        SolutionFile::write_header(out, &self.puzzle, self.shard.unwrap_or(Shard::ALL))?;
+        if let Some(completed) = self.completed_units {
+            writeln!(out, "# completed-units {completed}")?;
+            if let Some(nodes) = self.stats.nodes_to_first_solution {
+                writeln!(out, "# nodes-to-first-solution {nodes}")?;
+            }
+        }
+        let mut last_unit = 0;
+        for (unit, solution) in &self.solutions {
+            SolutionFile::write_unit(out, &mut last_unit, *unit)?;
+            writeln!(out, "{solution}")?;
+        }
+        SolutionFile::write_footer(out, self.solutions.len(), &self.stats)
+    }
+
+    /// Writes the file to `path`, replacing it only once the new contents are complete, so that a
+    /// checkpoint survives the run being killed while it is written
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let partial = path.with_extension("partial");
+        let mut out = io::BufWriter::new(fs::File::create(&partial)?);
+        self.write(&mut out)?;
+        out.flush()?;
+        drop(out);
+        fs::rename(partial, path)
+    }
+
+    /// Reads a solution file or checkpoint, checking that it is complete
+    pub fn read(path: PathBuf) -> io::Result<SolutionFile> {
+        let invalid = |message: String| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("{}: {message}", path.display()))
+        };
+        let contents = fs::read_to_string(&path)?;
+        let mut file = SolutionFile::default();
+        let mut total = None;
+        let mut unit = 0;
+        for line in contents.lines() {
+            let Some(comment) = line.strip_prefix("# ") else {
+                file.solutions.push((unit, line.to_string()));
+                continue;
+            };
+            let (key, value) = comment.split_once(' ').unwrap_or((comment, ""));
+            let number = || value.parse::<u64>().map_err(|_| invalid(format!("invalid {key} {value:?}")));
+            match key {
+                "puzzle" => file.puzzle = value.to_string(),
+                "shard" => file.shard = Some(value.parse().map_err(invalid)?),
+                "solutions" => total = Some(number()? as usize),
+                "nodes" => file.stats.nodes = number()?,
+                "placements" => file.stats.placements = number()?,
+                "overlaps" => file.stats.overlaps = number()?,
+                "uncovered-cells" => file.stats.uncovered_cells = number()?,
+                "unplaceable-pieces" => file.stats.unplaceable_pieces = number()?,
+                "isolated-regions" => file.stats.isolated_regions = number()?,
+                "depth-nodes" => {
+                    let depths: Vec<&str> = value.split_whitespace().collect();
+                    if depths.len() > file.stats.depth_nodes.len() {
+                        return Err(invalid(format!("invalid {key} {value:?}")));
+                    }
+                    for (nodes, depth) in file.stats.depth_nodes.iter_mut().zip(depths) {
+                        *nodes = depth.parse().map_err(|_| invalid(format!("invalid {key} {value:?}")))?;
+                    }
+                }
+                "unit" => unit = number()? as usize,
+                "completed-units" => file.completed_units = Some(number()? as usize),
+                "nodes-to-first-solution" => file.stats.nodes_to_first_solution = Some(number()?),
+                _ => {}
+            }
+        }
+        match total {
+            None => Err(invalid("no solution count, the run did not finish".to_string())),
+            Some(total) if total != file.solutions.len() => Err(invalid(format!(
+                "{} solutions listed but {total} counted",
+                file.solutions.len()
+            ))),
+            Some(_) => Ok(file),
+        }
+    }
+
+    /// Combines the files of every shard of one run into the file of the whole run, with the
+    /// solutions in work unit order
+    pub fn merge(mut files: Vec<SolutionFile>) -> io::Result<SolutionFile> {
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidInput, message);
+        let first = files.first().ok_or_else(|| invalid("no files to merge".to_string()))?;
+        let puzzle = first.puzzle.clone();
+        let count = first.shard.unwrap_or(Shard::ALL).count;
+        if let Some(file) = files.iter().find(|file| file.puzzle != puzzle) {
+            return Err(invalid(format!("cannot merge solutions of {puzzle} and {}", file.puzzle)));
+        }
+        if files.iter().any(|file| file.completed_units.is_some()) {
+            return Err(invalid("cannot merge a checkpoint of an unfinished run".to_string()));
+        }
+
+        files.sort_by_key(|file| file.shard.unwrap_or(Shard::ALL).index);
+        let shards: Vec<Shard> = files.iter().map(|file| file.shard.unwrap_or(Shard::ALL)).collect();
+        let expected: Vec<Shard> = (1..=count).map(|index| Shard { index, count }).collect();
+        if shards != expected {
+            let given: Vec<String> = shards.iter().map(Shard::to_string).collect();
+            return Err(invalid(format!(
+                "expected shards 1/{count} to {count}/{count} once each, got {}",
+                given.join(", ")
+            )));
+        }
+
+        let mut merged = SolutionFile {
+            puzzle,
+            ..SolutionFile::default()
+        };
+        for file in files {
+            merged.solutions.extend(file.solutions);
+            merged.stats += file.stats;
+        }
+        // Stable, so the solutions of each unit keep the order they were found in
+        merged.solutions.sort_by_key(|(unit, _)| *unit);
+        Ok(merged)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_shard() {
+        assert_eq!("2/8".parse(), Ok(Shard { index: 2, count: 8 }));
+        assert_eq!(" 1 / 1 ".parse(), Ok(Shard::ALL));
+        for invalid in ["0/4", "5/4", "2", "a/4", "2/", ""] {
+            assert!(invalid.parse::<Shard>().is_err(), "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn shards_cover_every_unit_once() {
+        let units: Vec<Vec<usize>> = (1..=3).map(|index| Shard { index, count: 3 }.units(10).collect()).collect();
+        assert_eq!(units, vec![vec![0, 3, 6, 9], vec![1, 4, 7], vec![2, 5, 8]]);
+        for shard in (1..=3).map(|index| Shard { index, count: 3 }) {
+            for (position, unit) in shard.units(10).enumerate() {
+                assert_eq!(shard.unit(position), unit);
+            }
+        }
+    }
+
+    #[test]
+    fn solution_file_round_trip() {
+        let mut stats = Statistics {
+            nodes: 13270,
+            placements: 472820,
+            isolated_regions: 10,
+            ..Statistics::default()
+        };
+        stats.depth_nodes[1] = 4;
+        let file = SolutionFile {
+            puzzle: "puzzles/soma-cube.csv 3x3x3 --reflected-pieces".to_string(),
+            shard: Some(Shard { index: 2, count: 3 }),
+            solutions: vec![
+                (1, "660631551640530531244224231".to_string()),
+                (4, "660631111620330544222354554".to_string()),
+            ],
+            stats,
+            completed_units: None,
+        };
+        let path = std::env::temp_dir().join(format!("bedlam-cube-round-trip-{}.txt", std::process::id()));
+        file.save(&path).unwrap();
+        let read = SolutionFile::read(path.clone());
+        fs::remove_file(&path).unwrap();
+        let read = read.unwrap();
+
+        assert_eq!(read.puzzle, file.puzzle);
+        assert_eq!(read.shard, file.shard);
+        assert_eq!(read.solutions, file.solutions);
+        assert_eq!(read.completed_units, file.completed_units);
+        assert_eq!(read.stats, stats);
+    }
+
+    #[test]
+    fn checkpoint_round_trip() {
+        let file = SolutionFile {
+            puzzle: "puzzles/soma-cube.csv 3x3x3 --unique".to_string(),
+            shard: Some(Shard::ALL),
+            solutions: vec![(0, "660631551640530531244224231".to_string())],
+            stats: Statistics {
+                nodes: 2851,
+                nodes_to_first_solution: Some(34),
+                ..Statistics::default()
+            },
+            completed_units: Some(8),
+        };
+        let path = std::env::temp_dir().join(format!("bedlam-cube-checkpoint-round-trip-{}.txt", std::process::id()));
+        file.save(&path).unwrap();
+        let read = SolutionFile::read(path.clone());
+        fs::remove_file(&path).unwrap();
+        let read = read.unwrap();
+
+        assert_eq!(read.puzzle, file.puzzle);
+        assert_eq!(read.solutions, file.solutions);
+        assert_eq!(read.completed_units, file.completed_units);
+        assert_eq!(read.stats, file.stats);
+    }
+
+    #[test]
+    fn merge_restores_unit_order() {
+        let shard = |index, solutions: &[(usize, &str)]| SolutionFile {
+            puzzle: "soma".to_string(),
+            shard: Some(Shard { index, count: 2 }),
+            solutions: solutions.iter().map(|&(unit, line)| (unit, line.to_string())).collect(),
+            ..SolutionFile::default()
+        };
+        let merged = SolutionFile::merge(vec![
+            shard(2, &[(1, "b"), (1, "c"), (5, "f")]),
+            shard(1, &[(0, "a"), (2, "d"), (4, "e")]),
+        ])
+        .unwrap();
+        let lines: Vec<&str> = merged.solutions.iter().map(|(_, line)| line.as_str()).collect();
+        assert_eq!(lines, ["a", "b", "c", "d", "e", "f"]);
+    }
+}
@@ -0,0 +1,132 @@
+use crate::puzzle::{Placement, Puzzle};
+use crate::symmetry::CellMap;
+
+use std::ops::ControlFlow;
+
+/// Symbols standing for the pieces in a solution written on one line, by piece index
+const SYMBOLS: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz+/";
+
+/// A complete assembly: the placement of every piece, ordered by piece index
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Solution<const W: usize = 1> {
+    pub placements: Vec<(usize, Placement<W>)>,
+}
+
+/// Receives each solution as it is found, and can stop the search by returning
+/// `ControlFlow::Break`
+pub type SolutionSink<'a, const W: usize> =
+    dyn FnMut(&Solution<W>) -> ControlFlow<()> + Send + 'a;
+
+impl<const W: usize> Solution<W> {
+    pub fn new(mut placements: Vec<(usize, Placement<W>)>) -> Solution<W> {
+        placements.sort_by_key(|(pid, _)| *pid);
+        Solution { placements }
+    }
+
+    /// Image of the solution under a symmetry of the box. Reflections swap each piece for its
+    /// mirror image in `mirrors`.
+    pub fn transform(&self, map: &CellMap, mirrors: &[usize]) -> Solution<W> {
+        Solution::new(
+            self.placements
+                .iter()
+                .map(|&(pid, placement)| {
+                    let pid = if map.is_reflection() { mirrors[pid] } else { pid };
+                    (pid, map.apply(placement))
+                })
+                .collect(),
+        )
+    }
+
+    /// Lexicographically smallest image of the solution under the rotations of the puzzle, and
+    /// also its reflections if `reflections` is set. Solutions related by one of these
+    /// symmetries share a canonical form.
+    pub fn canonical(&self, puzzle: &Puzzle<W>, reflections: bool) -> Solution<W> {
+        puzzle
+            .symmetries
+            .iter()
+            .filter(|map| reflections || !map.is_reflection())
+            .map(|map| self.transform(map, &puzzle.mirrors))
+            .min()
+            .unwrap_or_else(|| self.clone())
+    }
+
+    /// Distinct images of the solution under the rotations of the puzzle, smallest first. These
+    /// are all solutions, and the ones `canonical` without reflections relates to it.
+    pub fn rotations(&self, puzzle: &Puzzle<W>) -> Vec<Solution<W>> {
+        let mut images: Vec<Solution<W>> = puzzle
+            .symmetries
+            .iter()
+            .filter(|map| !map.is_reflection())
+            .map(|map| self.transform(map, &puzzle.mirrors))
+            .collect();
+        images.sort();
+        images.dedup();
+        images
+    }
+
+    /// The solution on one line: for each cell of the box in index order, the piece covering
+    /// it as a digit, letter, `+` or `/`, or `.` if the cell is left empty
+    pub fn to_line(&self, puzzle: &Puzzle<W>) -> String {
+        let mut line = vec![b'.'; puzzle.dim.volume()];
+        for &(pid, placement) in &self.placements {
+            for cell in placement.ones() {
+                line[cell] = SYMBOLS[pid];
+            }
+        }
+        String::from_utf8(line).unwrap()
+    }
+
+    /// Reads back a solution written by `to_line`, if the line is one for the puzzle
+    pub fn from_line(line: &str, puzzle: &Puzzle<W>) -> Option<Solution<W>> {
+        if line.len() != puzzle.dim.volume() {
+            return None;
+        }
+        let mut placements = vec![Placement::new(); puzzle.pieces.len()];
+        for (cell, symbol) in line.bytes().enumerate() {
+            if symbol != b'.' {
+                let pid = SYMBOLS.iter().position(|&s| s == symbol)?;
+                placements.get_mut(pid)?.set(cell);
+            }
+        }
+        Some(Solution::new(placements.into_iter().enumerate().collect()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::puzzle::Coord;
+    use crate::target::Target;
+
+    use std::fs;
+
+    /// Two dominoes in a 2x2x1 box
+    fn dominoes() -> Puzzle {
+        let path = std::env::temp_dir().join(format!("bedlam-cube-dominoes-{}.csv", std::process::id()));
+        fs::write(&path, "Dominoes,Color,Positions\nA,red,000-100\nB,blue,000-100\n").unwrap();
+        let puzzle = Puzzle::from_csv(path.clone(), &Target::cuboid(Coord::new(2, 2, 1)), false);
+        fs::remove_file(&path).unwrap();
+        puzzle.unwrap()
+    }
+
+    #[test]
+    fn canonical_of_symmetric_solution() {
+        let puzzle = dominoes();
+        // Turning the box over about the y axis maps each domino onto itself
+        let solution = Solution::from_line("0011", &puzzle).unwrap();
+        let rotations = solution.rotations(&puzzle);
+        assert_eq!(rotations.len(), 4);
+        assert!(rotations.contains(&solution));
+
+        let canonical = solution.canonical(&puzzle, false);
+        assert_eq!(canonical, rotations[0]);
+        for rotation in &rotations {
+            assert_eq!(rotation.canonical(&puzzle, false), canonical);
+        }
+        let lines: Vec<String> = rotations.iter().map(|rotation| rotation.to_line(&puzzle)).collect();
+        assert_eq!(lines.len(), 4);
+        for line in ["0011", "1100", "0101", "1010"] {
+            assert!(lines.contains(&line.to_string()), "{line}");
+        }
+    }
+}
@@ -1,182 +1,968 @@
-use crate::puzzle::{Arrangement, Bitset, Board, Coord, Orientation, Placement, Puzzle};
+use crate::cancel::CancellationToken;
+use crate::dlx::DancingLinks;
+use crate::estimate::{Estimate, Mean};
+use crate::progress::Progress;
+use crate::puzzle::{Arrangement, Bitset, Board, Coord, PieceSet, Placement, Puzzle, MAX_PIECES};
+use crate::random::Random;
+use crate::shard::Shard;
+use crate::solution::{Solution, SolutionSink};
+use crate::stats::Statistics;
+use crate::trace::Trace;
 
-use std::ops::BitAnd;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::{BitAnd, ControlFlow};
 use std::simd::cmp::SimdPartialEq;
 use std::simd::num::SimdUint;
 use std::simd::u64x8;
-use std::time::Instant;
+use std::simd::Select;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use rayon::prelude::*;
 
 
+/// Search strategy that enumerates the ways of completing a partial arrangement. The solver
+/// removes symmetric starting positions and hands each starting arrangement to the strategy.
+pub trait SolveStrategy<const W: usize> {
+    /// Calls `found` with every solution that extends `start` by placing each piece in
+    /// `remaining` exactly once, until `found` breaks or `cancel` is cancelled. The top-level
+    /// branches of the search and the nodes they visit are recorded in `progress` as they finish.
+    #[allow(clippy::too_many_arguments)]
+    fn solve(
+        &self,
+        puzzle: &Puzzle<W>,
+        start: &Arrangement<W>,
+        remaining: &[usize],
+        found: &mut SolutionSink<W>,
+        stats: &mut Statistics,
+        cancel: &CancellationToken,
+        progress: &Progress,
+    ) -> ControlFlow<()>;
+}
+
+/// Built-in search strategies
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Algorithm {
+    /// Backtracking over bitset boards, filling one empty cell at a time
+    #[default]
+    Bitset,
+    /// Knuth's Dancing Links over the exact cover matrix of the puzzle
+    Dlx,
+}
+
+impl Algorithm {
+    pub fn strategy<const W: usize>(self) -> Box<dyn SolveStrategy<W>> {
+        match self {
+            Algorithm::Bitset => Box::new(Backtracker::default()),
+            Algorithm::Dlx => Box::new(DancingLinks),
+        }
+    }
+}
+
+/// Policy for choosing which empty cell the backtracker fills next
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum CellOrder {
+    /// Lowest empty cell index
+    #[default]
+    Linear,
+    /// The corners of the box first, then outward by distance to the nearest corner
+    CornersFirst,
+    /// Cells on the surface of the target first, then inward layer by layer
+    SurfaceFirst,
+    /// Cells closest to the centre of the box first
+    CentreOutward,
+    /// Empty cell covered by the fewest placements of the remaining pieces
+    MostConstrained,
+}
+
+/// Order in which the backtracker tries the placements that fill the chosen cell
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum MoveOrder {
+    /// Piece by piece, in the order the placements were generated
+    #[default]
+    Natural,
+    /// Placements leaving the most entropy (see `Solver::entropy`) first, looking ahead
+    /// `lookahead` moves
+    Entropy,
+}
+
+/// Checks the backtracker makes before placing a piece, in the order it makes them
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Check {
+    /// The placement overlaps a piece already placed
+    Overlap,
+    /// The remaining pieces could no longer cover some empty cell
+    UncoveredCells,
+    /// Some remaining piece would no longer fit anywhere
+    UnplaceablePieces,
+    /// A region of empty cells could not be filled by the remaining pieces
+    IsolatedRegion,
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Check::Overlap => "overlap",
+            Check::UncoveredCells => "uncovered cells",
+            Check::UnplaceablePieces => "unplaceable pieces",
+            Check::IsolatedRegion => "isolated region",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Backtracking search over bitset boards
+#[derive(Clone, Copy, Debug)]
+pub struct Backtracker {
+    pub cell_order: CellOrder,
+    pub move_order: MoveOrder,
+    /// Number of moves the entropy ordering looks ahead, at least 1
+    pub lookahead: usize,
+    /// Reject placements that leave a region of empty cells the remaining pieces cannot fill
+    pub prune_isolated: bool,
+    /// Number of pieces placed on top of each starting arrangement before the search is split
+    /// into tasks shared between threads. 0 searches each starting arrangement on one thread.
+    pub split_depth: usize,
+    /// Count the search nodes at each depth and the placements rejected by each check in
+    /// `Statistics`, for `Report`
+    pub instrument: bool,
+}
+
+impl Default for Backtracker {
+    fn default() -> Self {
+        Backtracker {
+            cell_order: CellOrder::default(),
+            move_order: MoveOrder::default(),
+            lookahead: 1,
+            prune_isolated: true,
+            split_depth: 2,
+            instrument: false,
+        }
+    }
+}
+
+/// Search node at the split depth, searched as one task: the pieces placed so far, the position
+/// of the last filled cell, the pieces still to place and the search nodes visited before it in
+/// a sequential search, apart from those of earlier units
+type WorkUnit<const W: usize> = (Path<W>, usize, PieceSet, u64);
+
+impl<const W: usize> SolveStrategy<W> for Backtracker {
+    fn solve(
+        &self,
+        puzzle: &Puzzle<W>,
+        start: &Arrangement<W>,
+        remaining: &[usize],
+        found: &mut SolutionSink<W>,
+        stats: &mut Statistics,
+        cancel: &CancellationToken,
+        progress: &Progress,
+    ) -> ControlFlow<()> {
+        let order = self.cell_sequence(puzzle);
+        let mut path = Path::new(start);
+        let remaining = remaining.iter().copied().collect();
+        let nodes = stats.nodes;
+        if self.split_depth == 0 {
+            progress.begin_branches(1);
+            let flow = self.solve_board(found, stats, cancel, puzzle, &order, &mut path, 0, remaining);
+            progress.finish_branch(stats.nodes - nodes);
+            return flow;
+        }
+
+        let mut units = vec![];
+        self.collect_units(stats, puzzle, &order, &mut path, 0, remaining, self.split_depth, &mut units);
+        progress.add_nodes(stats.nodes - nodes);
+        progress.begin_branches(units.len());
+
+        // Rayon's work stealing balances the units between threads. Solutions reach the sink in
+        // unit order, so the output is the same for any number of threads: those of the first
+        // unfinished unit straight away, and those of later units once every unit before them
+        // has finished. Once the sink breaks, the other workers stop at their next node.
+        let stopped = cancel.child();
+        let output = Mutex::new(OrderedOutput {
+            found,
+            next: 0,
+            pending: BTreeMap::new(),
+            nodes: 0,
+            nodes_to_first_solution: None,
+        });
+        let unit_stats = Mutex::new(Statistics::default());
+        let flow = units.into_par_iter().enumerate().try_for_each(|(unit, (mut path, prev, remaining, before))| {
+            let mut stats = Statistics::default();
+            let mut solutions = vec![];
+            let mut found = |solution: &Solution<W>| output.lock().unwrap().found(unit, solution, &mut solutions);
+            let mut flow = self.solve_board(&mut found, &mut stats, &stopped, puzzle, &order, &mut path, prev, remaining);
+            progress.finish_branch(stats.nodes);
+            *unit_stats.lock().unwrap() += stats;
+
+            let mut output = output.lock().unwrap();
+            if flow.is_continue() {
+                flow = output.finish(unit, solutions, before, &stats);
+            } else {
+                output.stop(unit, before, &stats);
+            }
+            if flow.is_break() {
+                stopped.cancel();
+            }
+            flow
+        });
+        // The units count their first solutions from their own start, and only the output knows
+        // which one comes first in search order
+        let unit_stats = unit_stats.into_inner().unwrap();
+        *stats += Statistics {
+            nodes_to_first_solution: None,
+            ..unit_stats
+        };
+        if stats.nodes_to_first_solution.is_none() {
+            stats.nodes_to_first_solution = output.into_inner().unwrap().nodes_to_first_solution;
+        }
+        flow
+    }
+}
+
+/// Solutions of finished work units, passed on to the sink in unit order
+struct OrderedOutput<'a, 'b, const W: usize> {
+    found: &'a mut SolutionSink<'b, W>,
+    /// First unit whose solutions have not been passed on yet
+    next: usize,
+    /// Solutions of the finished units after `next`, with the nodes above the split before them
+    /// and their statistics
+    pending: BTreeMap<usize, (Vec<Solution<W>>, u64, Statistics)>,
+    /// Search nodes of the units before `next`
+    nodes: u64,
+    /// Search nodes up to and including the first solution passed on, counted as in a
+    /// sequential search
+    nodes_to_first_solution: Option<u64>,
+}
+
+impl<const W: usize> OrderedOutput<'_, '_, W> {
+    /// Passes on a solution of `unit` straight away, after those kept in `buffer`, if every
+    /// unit before it has finished, and otherwise keeps it in `buffer`
+    fn found(&mut self, unit: usize, solution: &Solution<W>, buffer: &mut Vec<Solution<W>>) -> ControlFlow<()> {
+        if unit != self.next {
+            buffer.push(solution.clone());
+            return ControlFlow::Continue(());
+        }
+        for solution in buffer.drain(..) {
+            (self.found)(&solution)?;
+        }
+        (self.found)(solution)
+    }
+
+    /// Records the first solution of a unit stopped before it finished, if its solutions were
+    /// being passed on
+    fn stop(&mut self, unit: usize, before: u64, stats: &Statistics) {
+        if unit == self.next && self.nodes_to_first_solution.is_none() {
+            self.nodes_to_first_solution = stats.nodes_to_first_solution.map(|nodes| before + self.nodes + nodes);
+        }
+    }
+
+    /// Records the solutions of a finished unit, and passes on those of every finished unit
+    /// that is no longer waiting for an earlier one
+    fn finish(
+        &mut self,
+        unit: usize,
+        solutions: Vec<Solution<W>>,
+        before: u64,
+        stats: &Statistics,
+    ) -> ControlFlow<()> {
+        self.pending.insert(unit, (solutions, before, *stats));
+        while let Some((solutions, before, stats)) = self.pending.remove(&self.next) {
+            if self.nodes_to_first_solution.is_none() {
+                self.nodes_to_first_solution =
+                    stats.nodes_to_first_solution.map(|nodes| before + self.nodes + nodes);
+            }
+            self.nodes += stats.nodes;
+            self.next += 1;
+            for solution in &solutions {
+                (self.found)(solution)?;
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+/// Pieces placed along the current search path, kept in fixed-size storage so that the search
+/// does not allocate
+#[derive(Clone, Copy)]
+struct Path<const W: usize> {
+    occupied: Board<W>,
+    placements: [(usize, Placement<W>); MAX_PIECES],
+    len: usize,
+}
+
+impl<const W: usize> Path<W> {
+    fn new(start: &Arrangement<W>) -> Path<W> {
+        let mut path = Path {
+            occupied: start.occupied,
+            placements: [(0, Placement::new()); MAX_PIECES],
+            len: 0,
+        };
+        for &(piece, placement) in &start.placements {
+            path.placements[path.len] = (piece, placement);
+            path.len += 1;
+        }
+        path
+    }
+
+    fn push(&mut self, piece: usize, placement: Placement<W>) {
+        self.occupied = self.occupied.union(placement);
+        self.placements[self.len] = (piece, placement);
+        self.len += 1;
+    }
+
+    fn pop(&mut self) {
+        self.len -= 1;
+        self.occupied = self.occupied.xor(self.placements[self.len].1);
+    }
+
+    fn solution(&self) -> Solution<W> {
+        Solution::new(self.placements[..self.len].to_vec())
+    }
+}
+
+impl Backtracker {
+    /// Cells in the order a static cell ordering fills them. The most-constrained ordering is
+    /// chosen during the search and uses the linear order here.
+    pub fn cell_sequence<const W: usize>(&self, puzzle: &Puzzle<W>) -> Vec<usize> {
+        let dim = puzzle.dim;
+        let mut cells: Vec<usize> = (0..dim.volume()).collect();
+        match self.cell_order {
+            CellOrder::Linear | CellOrder::MostConstrained => {}
+            CellOrder::CornersFirst => {
+                let corners = puzzle.corners();
+                cells.sort_by_key(|&cell| {
+                    let c = Coord::from_index(cell, dim);
+                    corners
+                        .iter()
+                        .map(|k| (c.x - k.x).abs() + (c.y - k.y).abs() + (c.z - k.z).abs())
+                        .min()
+                });
+            }
+            CellOrder::SurfaceFirst => {
+                // Breadth first from the target cells with a face outside the target
+                let mut depth = vec![usize::MAX; dim.volume()];
+                let mut layer: Vec<usize> = puzzle
+                    .target
+                    .ones()
+                    .filter(|&cell| puzzle.neighbours[cell].intersection(puzzle.target).count() < 6)
+                    .collect();
+                let mut d = 0;
+                while !layer.is_empty() {
+                    for &cell in &layer {
+                        depth[cell] = d;
+                    }
+                    let mut next = vec![];
+                    for &cell in &layer {
+                        for n in puzzle.neighbours[cell].intersection(puzzle.target).ones() {
+                            if depth[n] == usize::MAX && !next.contains(&n) {
+                                next.push(n);
+                            }
+                        }
+                    }
+                    layer = next;
+                    d += 1;
+                }
+                cells.sort_by_key(|&cell| depth[cell]);
+            }
+            CellOrder::CentreOutward => {
+                // Doubled coordinates keep the centre of an even side on the grid
+                cells.sort_by_key(|&cell| {
+                    let c = Coord::from_index(cell, dim);
+                    [(c.x, dim.x), (c.y, dim.y), (c.z, dim.z)]
+                        .iter()
+                        .map(|&(v, side)| (2 * v - (side - 1)).pow(2))
+                        .sum::<i64>()
+                });
+            }
+        }
+        cells
+    }
+
+    /// Picks the next cell to fill, returning its position in `order` (or the cell itself for
+    /// the most-constrained ordering) and the cell
+    fn new_cube<const W: usize>(
+        &self,
+        puzzle: &Puzzle<W>,
+        order: &[usize],
+        occupied: Board<W>,
+        prev: usize,
+        remaining: PieceSet,
+    ) -> Option<(usize, usize)> {
+        let (next, cube) = match self.cell_order {
+            CellOrder::Linear | CellOrder::CornersFirst | CellOrder::SurfaceFirst | CellOrder::CentreOutward => {
+                // Every cell before the previously filled one is already occupied
+                let mut next = prev;
+                while next < order.len() && occupied.get(order[next]) {
+                    next += 1;
+                }
+                if next == order.len() {
+                    return None;
+                }
+                (next, order[next])
+            }
+            CellOrder::MostConstrained => {
+                let mut best: Option<(usize, usize)> = None;
+                for cube in puzzle.full.difference(occupied).ones() {
+                    let options = Solver::number_orientations_for_coord(
+                        puzzle,
+                        occupied,
+                        remaining,
+                        Coord::from_index(cube, puzzle.dim),
+                    );
+                    if best.is_none_or(|(_, fewest)| options < fewest) {
+                        best = Some((cube, options));
+                    }
+                    if options <= 1 {
+                        // Cannot do better, and a cell nothing covers is a dead end
+                        break;
+                    }
+                }
+                let cube = best?.0;
+                (cube, cube)
+            }
+        };
+
+        Some((next, cube))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn solve_board<const W: usize>(
+        &self,
+        found: &mut SolutionSink<W>,
+        stats: &mut Statistics,
+        cancel: &CancellationToken,
+        puzzle: &Puzzle<W>,
+        order: &[usize],
+        path: &mut Path<W>,
+        prev: usize,
+        remaining: PieceSet,
+    ) -> ControlFlow<()> {
+        if cancel.is_cancelled() {
+            return ControlFlow::Break(());
+        }
+        stats.nodes += 1;
+        if self.instrument {
+            stats.depth_nodes[path.len] += 1;
+        }
+        if remaining.is_empty() {
+            stats.nodes_to_first_solution.get_or_insert(stats.nodes);
+            return found(&path.solution());
+        }
+
+        let (next, cube) = match self.new_cube(puzzle, order, path.occupied, prev, remaining) {
+            Some((c, m)) => (c, m),
+            None => return ControlFlow::Continue(()),
+        };
+
+        if self.move_order == MoveOrder::Entropy {
+            for (pid, placement) in self.entropy_ordered_moves(puzzle, path.occupied, remaining, cube) {
+                self.check_next_piece(found, stats, cancel, puzzle, order, remaining, pid, &[placement], path, next)?;
+            }
+            ControlFlow::Continue(())
+        } else {
+            for pid in remaining.iter() {
+                let placements = &puzzle.lookup[cube][pid];
+                self.check_next_piece(found, stats, cancel, puzzle, order, remaining, pid, placements, path, next)?;
+            }
+            ControlFlow::Continue(())
+        }
+    }
+    /// Placements of the remaining pieces that fill `cube`, as `(piece, placement)`, ordered by
+    /// decreasing entropy after looking ahead `self.lookahead` moves
+    fn entropy_ordered_moves<const W: usize>(
+        &self,
+        puzzle: &Puzzle<W>,
+        board: Board<W>,
+        remaining: PieceSet,
+        cube: usize,
+    ) -> Vec<(usize, Placement<W>)> {
+        let mut scored = vec![];
+        for pid in remaining.iter() {
+            for &placement in &puzzle.lookup[cube][pid] {
+                if board.intersects(placement) {
+                    continue;
+                }
+                let score = self.lookahead_entropy(
+                    puzzle,
+                    board.union(placement),
+                    remaining.without(pid),
+                    self.lookahead.max(1) - 1,
+                );
+                scored.push((score, (pid, placement)));
+            }
+        }
+        scored.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+        scored.into_iter().map(|(_, m)| m).collect()
+    }
+
+    /// Highest entropy reachable from `board` in `depth` more moves, filling the lowest empty
+    /// cell at each move
+    fn lookahead_entropy<const W: usize>(
+        &self,
+        puzzle: &Puzzle<W>,
+        board: Board<W>,
+        pieces: PieceSet,
+        depth: usize,
+    ) -> f64 {
+        let entropy = Solver::entropy(puzzle, board, pieces);
+        if depth == 0 || pieces.is_empty() || entropy == f64::NEG_INFINITY {
+            return entropy;
+        }
+
+        let cube = match puzzle.full.difference(board).first() {
+            Some(cube) => cube,
+            None => return entropy,
+        };
+        let mut best = f64::NEG_INFINITY;
+        for pid in pieces.iter() {
+            for &placement in &puzzle.lookup[cube][pid] {
+                if !board.intersects(placement) {
+                    let score =
+                        self.lookahead_entropy(puzzle, board.union(placement), pieces.without(pid), depth - 1);
+                    best = best.max(score);
+                }
+            }
+        }
+        best
+    }
+
+    /// Searches the tree down to `depth` more pieces like `solve_board`, collecting the nodes
+    /// reached there, and any solutions found above them, as work units in search order
+    #[allow(clippy::too_many_arguments)]
+    fn collect_units<const W: usize>(
+        &self,
+        stats: &mut Statistics,
+        puzzle: &Puzzle<W>,
+        order: &[usize],
+        path: &mut Path<W>,
+        prev: usize,
+        remaining: PieceSet,
+        depth: usize,
+        units: &mut Vec<WorkUnit<W>>,
+    ) {
+        if depth == 0 || remaining.is_empty() {
+            units.push((*path, prev, remaining, stats.nodes));
+            return;
+        }
+        stats.nodes += 1;
+        if self.instrument {
+            stats.depth_nodes[path.len] += 1;
+        }
+
+        let (next, cube) = match self.new_cube(puzzle, order, path.occupied, prev, remaining) {
+            Some((c, m)) => (c, m),
+            None => return,
+        };
+        for (pid, placement) in self.moves(puzzle, path.occupied, remaining, cube) {
+            let other_pieces = remaining.without(pid);
+            if self.accepts(stats, puzzle, path.occupied, placement, other_pieces) {
+                path.push(pid, placement);
+                self.collect_units(stats, puzzle, order, path, next, other_pieces, depth - 1, units);
+                path.pop();
+            }
+        }
+    }
+
+    /// Placements of the remaining pieces that fill `cube`, as `(piece, placement)`, in the
+    /// order `solve_board` tries them
+    fn moves<const W: usize>(
+        &self,
+        puzzle: &Puzzle<W>,
+        occupied: Board<W>,
+        remaining: PieceSet,
+        cube: usize,
+    ) -> Vec<(usize, Placement<W>)> {
+        match self.move_order {
+            MoveOrder::Entropy => self.entropy_ordered_moves(puzzle, occupied, remaining, cube),
+            MoveOrder::Natural => remaining
+                .iter()
+                .flat_map(|pid| puzzle.lookup[cube][pid].iter().map(move |&placement| (pid, placement)))
+                .collect(),
+        }
+    }
+
+    /// Whether `placement` fits on `occupied` and leaves a board that `other_pieces` might still
+    /// complete
+    fn accepts<const W: usize>(
+        &self,
+        stats: &mut Statistics,
+        puzzle: &Puzzle<W>,
+        occupied: Board<W>,
+        placement: Placement<W>,
+        other_pieces: PieceSet,
+    ) -> bool {
+        self.rejection(stats, puzzle, occupied, placement, other_pieces).is_none()
+    }
+
+    /// The check that rejects `placement` on `occupied`, if any, counted in `stats`
+    fn rejection<const W: usize>(
+        &self,
+        stats: &mut Statistics,
+        puzzle: &Puzzle<W>,
+        occupied: Board<W>,
+        placement: Placement<W>,
+        other_pieces: PieceSet,
+    ) -> Option<Check> {
+        let check = self.failed_check(puzzle, occupied, placement, other_pieces);
+        if self.instrument {
+            stats.placements += 1;
+            match check {
+                Some(Check::Overlap) => stats.overlaps += 1,
+                Some(Check::UncoveredCells) => stats.uncovered_cells += 1,
+                Some(Check::UnplaceablePieces) => stats.unplaceable_pieces += 1,
+                Some(Check::IsolatedRegion) | None => {}
+            }
+        }
+        if check == Some(Check::IsolatedRegion) {
+            stats.isolated_regions += 1;
+        }
+        check
+    }
+
+    /// The first check, in the order they are made, that rejects `placement` on `occupied`
+    fn failed_check<const W: usize>(
+        &self,
+        puzzle: &Puzzle<W>,
+        occupied: Board<W>,
+        placement: Placement<W>,
+        other_pieces: PieceSet,
+    ) -> Option<Check> {
+        if occupied.intersects(placement) {
+            return Some(Check::Overlap);
+        }
+        let new_board = occupied.union(placement);
+        if !Solver::has_full_coverage(puzzle, new_board, other_pieces) {
+            return Some(Check::UncoveredCells);
+        }
+        if !Solver::can_pieces_fit(puzzle, new_board, other_pieces) {
+            return Some(Check::UnplaceablePieces);
+        }
+        if self.prune_isolated && !Solver::regions_fillable(puzzle, new_board, other_pieces) {
+            return Some(Check::IsolatedRegion);
+        }
+        None
+    }
+
+    /// One probe of Knuth's estimator: follows a random path down from `path`, choosing
+    /// uniformly between the moves `solve_board` would try at each node. Returns unbiased
+    /// estimates of the nodes and solutions of the tree below `path`, and of the seconds it
+    /// takes to search it.
+    fn probe<const W: usize>(
+        &self,
+        puzzle: &Puzzle<W>,
+        order: &[usize],
+        mut path: Path<W>,
+        mut remaining: PieceSet,
+        random: &mut Random,
+    ) -> (f64, f64, f64) {
+        let mut stats = Statistics::default();
+        let mut moves = vec![];
+        let mut prev = 0;
+        // Product of the branching factors above the current node: the number of nodes at its
+        // depth if the tree were uniform
+        let mut weight = 1.0;
+        let mut nodes = 0.0;
+        let mut seconds = 0.0;
+        loop {
+            nodes += weight;
+            if remaining.is_empty() {
+                return (nodes, weight, seconds);
+            }
+            // Each node costs about as much as here, where the moves are checked as in
+            // `solve_board`
+            let node_time = Instant::now();
+            let Some((next, cube)) = self.new_cube(puzzle, order, path.occupied, prev, remaining) else {
+                return (nodes, 0.0, seconds);
+            };
+            moves.clear();
+            for pid in remaining.iter() {
+                for &placement in &puzzle.lookup[cube][pid] {
+                    if self.accepts(&mut stats, puzzle, path.occupied, placement, remaining.without(pid)) {
+                        moves.push((pid, placement));
+                    }
+                }
+            }
+            seconds += weight * node_time.elapsed().as_secs_f64();
+            if moves.is_empty() {
+                return (nodes, 0.0, seconds);
+            }
+
+            let (pid, placement) = moves[random.below(moves.len())];
+            weight *= moves.len() as f64;
+            path.push(pid, placement);
+            remaining = remaining.without(pid);
+            prev = next;
+        }
+    }
+
+    /// Searches the tree below `path` like `solve_board`, adding a child of trace node `node` for
+    /// every placement tried that does not overlap the pieces already placed, down to `depth`
+    /// more pieces. Returns the number of solutions below `node`.
+    #[allow(clippy::too_many_arguments)]
+    fn trace_board<const W: usize>(
+        &self,
+        trace: &mut Trace,
+        node: usize,
+        puzzle: &Puzzle<W>,
+        order: &[usize],
+        path: &mut Path<W>,
+        prev: usize,
+        remaining: PieceSet,
+        depth: usize,
+        cancel: &CancellationToken,
+    ) -> u64 {
+        if remaining.is_empty() {
+            return 1;
+        }
+        let mut stats = Statistics::default();
+        if depth == 0 {
+            // Count the solutions below without recording the nodes
+            trace.nodes[node].expanded = false;
+            let mut solutions = 0;
+            let _ = self.solve_board(
+                &mut |_| {
+                    solutions += 1;
+                    ControlFlow::Continue(())
+                },
+                &mut stats,
+                cancel,
+                puzzle,
+                order,
+                path,
+                prev,
+                remaining,
+            );
+            return solutions;
+        }
+        let Some((next, cube)) = self.new_cube(puzzle, order, path.occupied, prev, remaining) else {
+            return 0;
+        };
+
+        let mut solutions = 0;
+        for (pid, placement) in self.moves(puzzle, path.occupied, remaining, cube) {
+            let other_pieces = remaining.without(pid);
+            let pruned = self.rejection(&mut stats, puzzle, path.occupied, placement, other_pieces);
+            if pruned == Some(Check::Overlap) {
+                continue;
+            }
+            let Some(child) = trace.push(node, pid, placement.ones().collect(), pruned) else {
+                break;
+            };
+            if pruned.is_none() {
+                path.push(pid, placement);
+                let found = self.trace_board(trace, child, puzzle, order, path, next, other_pieces, depth - 1, cancel);
+                path.pop();
+                trace.nodes[child].solutions = found;
+                solutions += found;
+            }
+            if cancel.is_cancelled() {
+                trace.truncated = true;
+            }
+            if trace.truncated {
+                break;
+            }
+        }
+        solutions
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn check_next_piece<const W: usize>(&self, found: &mut SolutionSink<W>, stats: &mut Statistics, cancel: &CancellationToken, puzzle: &Puzzle<W>, order: &[usize], remaining: PieceSet, pid: usize, placements: &[Placement<W>], path: &mut Path<W>, next: usize) -> ControlFlow<()> {
+        let other_pieces = remaining.without(pid);
+        for &placement in placements {
+            if self.accepts(stats, puzzle, path.occupied, placement, other_pieces) {
+                path.push(pid, placement);
+                let flow = self.solve_board(found, stats, cancel, puzzle, order, path, next, other_pieces);
+                path.pop();
+                flow?;
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
 pub struct Solver {
-    start_time: Instant,
+    /// Counters from the last search
+    pub stats: Statistics,
+    /// Stops the search early once cancelled, from any thread
+    pub cancel: CancellationToken,
+    /// How far the current search has got, for display from another thread
+    pub progress: Arc<Progress>,
+    /// Time spent on each starting arrangement of the last search, or on each work unit of a
+    /// shard
+    pub durations: Vec<Duration>,
 }
 
 impl Solver {
     pub fn build() -> Solver {
         Solver {
-            start_time: Instant::now(),
+            stats: Statistics::default(),
+            cancel: CancellationToken::new(),
+            progress: Arc::new(Progress::default()),
+            durations: vec![],
         }
     }
 
-    fn process_placement_chunk(board: Board, placements: &u64x8, coverage: u64) -> u64 {
-        let intersects = u64x8::splat(board.0).bitand(placements); // SIMD intersection
+    fn process_placement_chunk<const W: usize>(
+        board: Board<W>,
+        placements: &[u64x8; W],
+        coverage: &mut Board<W>,
+    ) {
+        let mut intersects = u64x8::splat(0);
+        for (word, chunk) in board.0.iter().zip(placements) {
+            intersects |= u64x8::splat(*word).bitand(chunk); // SIMD intersection
+        }
 
         let has_intersected = intersects.simd_eq(u64x8::splat(0));
 
-        let selected = has_intersected.select(*placements, u64x8::splat(0));
-
-        let reduced = selected.reduce_or();
+        for (word, chunk) in coverage.0.iter_mut().zip(placements) {
+            let selected = has_intersected.select(*chunk, u64x8::splat(0));
 
-        coverage | reduced
+            *word |= selected.reduce_or();
+        }
     }
 
-    pub fn has_full_coverage(
-        puzzle: &Puzzle,
-        board: Bitset,
-        pieces: &Vec<usize>,
+    pub fn has_full_coverage<const W: usize>(
+        puzzle: &Puzzle<W>,
+        board: Bitset<W>,
+        pieces: PieceSet,
     ) -> bool {
-        let mut coverage = board.clone().0;
+        let mut coverage = board;
 
-        for pid in pieces {
-            let piece = &puzzle.pieces[*pid];
+        for pid in pieces.iter() {
+            let piece = &puzzle.pieces[pid];
             for chunk in piece.simd_placements() {
-                coverage = Self::process_placement_chunk(board, chunk, coverage);
+                Self::process_placement_chunk(board, chunk, &mut coverage);
 
-                if coverage == Board::MAX {
+                if coverage == puzzle.full {
                     return true;
                 }
             }
         }
 
-        coverage == Board::MAX
+        coverage == puzzle.full
     }
 
-    pub fn number_orientations_for_coord(
-        puzzle: &Puzzle,
-        board: Bitset,
-        pieces: &Vec<usize>,
+    pub fn number_orientations_for_coord<const W: usize>(
+        puzzle: &Puzzle<W>,
+        board: Bitset<W>,
+        pieces: PieceSet,
         coord: Coord,
     ) -> usize {
+        let placements = &puzzle.lookup[coord.to_index(puzzle.dim)];
         pieces
             .iter()
-            .map(|pid| {
-                let piece = &puzzle.pieces[*pid];
-                piece
-                    .placements
-                    .iter()
-                    .filter(|placement: &&Placement| !board.intersects(**placement))
-                    .filter(|placement: &&Placement| placement.get(coord.to_index()))
-                    .count()
-            })
+            .map(|pid| placements[pid].iter().filter(|&&placement| !board.intersects(placement)).count())
             .sum()
     }
 
-    pub fn can_pieces_fit(puzzle: &Puzzle, board: Bitset, pieces: &Vec<usize>) -> bool {
-        for pid in pieces {
-            let piece = &puzzle.pieces[*pid];
+    pub fn can_pieces_fit<const W: usize>(
+        puzzle: &Puzzle<W>,
+        board: Bitset<W>,
+        pieces: PieceSet,
+    ) -> bool {
+        for pid in pieces.iter() {
+            let piece = &puzzle.pieces[pid];
             let length = piece
                 .placements
                 .iter()
-                .filter(|placement: &&Placement| !board.intersects(**placement))
+                .filter(|placement: &&Placement<W>| !board.intersects(**placement))
                 .count();
             if length == 0 {
                 return false;
             }
         }
-        return true;
+        true
     }
 
-    fn new_cube(
-        // &self,
-        _puzzle: &Puzzle,
-        arrangement: &Arrangement,
-        prev: usize,
-    ) -> Option<(usize, Bitset)> {
-        let mut cube = prev;
-        let mut mask = 1 << cube;
-
-        while mask & arrangement.occupied.0 != 0 {
-            cube += 1;
-            mask <<= 1;
+    /// Entropy of a board: the sum over the empty cells of log2 of the number of placements of
+    /// `pieces` that could still fill the cell. Higher entropy leaves the remaining pieces more
+    /// freedom. Boards that can no longer be completed have an entropy of minus infinity.
+    pub fn entropy<const W: usize>(puzzle: &Puzzle<W>, board: Bitset<W>, pieces: PieceSet) -> f64 {
+        if !Solver::has_full_coverage(puzzle, board, pieces) {
+            return f64::NEG_INFINITY;
         }
-
-        // do a check to ensure not isolated cube
-
-        Some((cube, Bitset(mask)))
+        puzzle
+            .full
+            .difference(board)
+            .ones()
+            .map(|cube| {
+                let coord = Coord::from_index(cube, puzzle.dim);
+                let options = Solver::number_orientations_for_coord(puzzle, board, pieces, coord);
+                (options as f64).log2()
+            })
+            .sum()
     }
 
-    fn solve_board(
-        solutions: &mut usize,
-        puzzle: &Puzzle,
-        arrangement: &mut Arrangement,
-        static_arrangement: &Arrangement,
-        prev: usize,
-        remaining: &Vec<usize>,
-    ) {
-        if remaining.is_empty() {
-            puzzle.show(&[arrangement, static_arrangement]);
-            println!();
-            *solutions += 1;
-            return;
-        }
-
-        let (cube, mask) = match Solver::new_cube(puzzle, arrangement, prev) {
-            Some((c, m)) => (c, m),
-            None => return,
-        };
-
-        if remaining.len() == 12 {
-            remaining.par_iter().enumerate().map(|(idx, pid)| {
-                let mut new_solutions = 0;
-                let mut new_arrangement = Arrangement::new();
-                new_arrangement.occupied = arrangement.occupied;
-                Solver::check_next_piece(&mut new_solutions, puzzle, remaining, idx, *pid, &mut new_arrangement, arrangement, mask, cube); // Check if clone is OK
-                new_solutions
-            }).collect::<Vec<_>>().iter().for_each(|s| *solutions += s);
-        } else {
-            for (idx, pid) in remaining.iter().enumerate() {
-                Solver::check_next_piece(solutions, puzzle, remaining, idx, *pid, arrangement, static_arrangement, mask, cube);
+    /// Checks that every connected region of empty cells could be filled exactly by some of
+    /// `pieces`: it must be at least as large as the smallest piece, and its size must be a sum of
+    /// piece volumes
+    pub fn regions_fillable<const W: usize>(
+        puzzle: &Puzzle<W>,
+        board: Bitset<W>,
+        pieces: PieceSet,
+    ) -> bool {
+        let empty = puzzle.full.difference(board);
+        let smallest = pieces.iter().map(|pid| puzzle.pieces[pid].volume()).min();
+        let mut sums: Option<Bitset<W>> = None;
+        let mut unvisited = empty;
+        while let Some(seed) = unvisited.first() {
+            let region = puzzle.region(unvisited, seed);
+            if region == empty {
+                // A single region is filled by all of the remaining pieces
+                return true;
             }
-        }
-    }
+            unvisited = unvisited.difference(region);
 
-    fn check_next_piece(solutions: &mut usize, puzzle: &Puzzle, remaining: &Vec<usize>, idx: usize, pid: usize, arrangement: &mut Arrangement, static_arrangement: &Arrangement, mask: Board, cube: usize) {
-        let mut other_pieces = remaining.clone();
-        other_pieces.remove(idx);
-        let piece = &puzzle.pieces[pid];
-        for &placement in piece.placements() {
-            let new_board = arrangement.occupied.union(placement);
-            if !arrangement.occupied.intersects(placement)
-                && placement.intersects(mask) // Check if the piece occupies next availiable board position
-                && Solver::has_full_coverage(puzzle, new_board, &other_pieces)
-                && Solver::can_pieces_fit(puzzle, new_board, &other_pieces)
-            {
-                arrangement.push(pid, placement);
-                Solver::solve_board(solutions, puzzle, arrangement, static_arrangement, cube, &other_pieces);
-                arrangement.pop();
+            let size = region.count();
+            if smallest.is_some_and(|smallest| size < smallest) {
+                return false;
+            }
+            let sums = sums.get_or_insert_with(|| {
+                // Subset sums of the remaining piece volumes, one bit per total. Regions are smaller
+                // than the board, so no total that matters is shifted out.
+                let mut sums = Bitset::single(0);
+                for pid in pieces.iter() {
+                    sums = sums.union(sums.shifted_up(puzzle.pieces[pid].volume()));
+                }
+                sums
+            });
+            if !sums.get(size) {
+                return false;
             }
         }
+        true
     }
 
-
-    fn constrain_start(&self, puzzle: &Puzzle) -> (usize, Vec<Arrangement>) {
-        let constrained_piece = puzzle
+    /// Piece with the fewest placements, which is fixed first to remove symmetric solutions
+    pub fn constrained_piece<const W: usize>(&self, puzzle: &Puzzle<W>) -> usize {
+        puzzle
             .pieces
             .iter()
             .enumerate()
-            .min_by(|(_, ref p1), (_, ref p2)| p1.placements().len().cmp(&p2.placements().len()))
-            .unwrap();
-        let mut unique_rotations: Vec<Board> = Vec::new();
+            .min_by(|(_, p1), (_, p2)| p1.placements().len().cmp(&p2.placements().len()))
+            .unwrap()
+            .0
+    }
+
+    fn constrain_start<const W: usize>(&self, puzzle: &Puzzle<W>) -> (usize, Vec<Arrangement<W>>) {
+        let constrained_piece = self.constrained_piece(puzzle);
+        let constrained_piece = (constrained_piece, &puzzle.pieces[constrained_piece]);
+        // Images of a placement under the rotations that map the target onto itself, starting
+        // with the placement itself
+        let rotations = |placement: Placement<W>| -> Vec<Board<W>> {
+            puzzle
+                .symmetries
+                .iter()
+                .filter(|map| !map.is_reflection())
+                .map(|map| map.apply(placement))
+                .collect()
+        };
+        let mut unique_rotations: Vec<Board<W>> = Vec::new();
         for placement in constrained_piece.1.placements() {
-            let mut unique = true;
-            for orientation in Orientation::from_placement(*placement).get_all_rotations(puzzle.dim)
-            {
-                if unique_rotations.contains(&Board::from_orientation(&orientation)) {
-                    unique = false;
-                    break;
-                }
-            }
+            let unique = rotations(*placement)
+                .iter()
+                .all(|board| !unique_rotations.contains(board));
             if unique {
                 unique_rotations.push(*placement);
             }
@@ -187,14 +973,12 @@ impl Solver {
         for placement in unique_rotations {
             let mut min_placements_count = usize::MAX;
             let mut min_placements = Placement::new();
-            for rotation in Orientation::from_placement(placement).get_all_rotations(puzzle.dim) {
-                let board = Board::from_orientation(&rotation);
-                let mut pieces: Vec<usize> = (0..puzzle.pieces.len()).collect();
-                pieces.remove(constrained_piece.0);
+            for board in rotations(placement) {
+                let pieces = PieceSet::first(puzzle.pieces.len()).without(constrained_piece.0);
                 let placement_count = Solver::number_orientations_for_coord(
                     puzzle,
                     board,
-                    &pieces,
+                    pieces,
                     Coord::new(0, 0, 0),
                 );
                 if placement_count < min_placements_count {
@@ -202,35 +986,199 @@ impl Solver {
                     min_placements_count = placement_count;
                 }
             }
-            let mut a = Arrangement::new();
+            let mut a = puzzle.arrangement();
             a.push(constrained_piece.0, min_placements);
             starting_arrangements.push(a)
         }
 
-        println!("{constrained_piece:?}");
         (constrained_piece.0, starting_arrangements)
     }
 
-    pub fn begin(&mut self, puzzle: &Puzzle) {
-        self.start_time = Instant::now();
-
+    /// Enumerates the solutions of the puzzle with `strategy`, passing each one to `found` until
+    /// it breaks or `self.cancel` is cancelled
+    pub fn begin<const W: usize>(
+        &mut self,
+        puzzle: &Puzzle<W>,
+        strategy: &dyn SolveStrategy<W>,
+        found: &mut SolutionSink<W>,
+    ) -> ControlFlow<()> {
+        self.stats = Statistics::default();
+        self.durations.clear();
         let (used_piece, starting_arrangements) = self.constrain_start(puzzle);
 
         let mut remaining: Vec<usize> = (0..puzzle.pieces.len()).collect();
         remaining.remove(used_piece);
-        let mut solutions = 0;
-        for a in starting_arrangements {
-            Solver::solve_board(&mut solutions, puzzle, &mut a.clone(), &Arrangement::new(), 0, &remaining)
-        }
-
-        // Print Information
-        let duration = Instant::now()
-            .duration_since(self.start_time)
-            .as_secs();
-        let s_per_solution = duration as f64 / solutions as f64;
-        println!("\n===== Statistics =====");
-        println!("Total Solutions: {}", solutions);
-        println!("Total Duration: {}s", duration);
-        println!("Rate: {:.2}ms per solution", s_per_solution * 1000.0);
+        let mut found = self.counted(found);
+        for (i, a) in starting_arrangements.iter().enumerate() {
+            self.progress.begin_start(i, starting_arrangements.len());
+            let start_time = Instant::now();
+            let flow = strategy.solve(puzzle, a, &remaining, &mut found, &mut self.stats, &self.cancel, &self.progress);
+            self.durations.push(start_time.elapsed());
+            flow?;
+        }
+        self.progress.begin_start(starting_arrangements.len(), starting_arrangements.len());
+        ControlFlow::Continue(())
+    }
+
+    /// Deterministic list of work units covering the whole search: each starting arrangement
+    /// with, in turn, every placement of a remaining piece that fills its lowest empty cell, and
+    /// the pieces left to place after it
+    pub fn work_units<const W: usize>(&self, puzzle: &Puzzle<W>) -> Vec<(Arrangement<W>, Vec<usize>)> {
+        let (used_piece, starting_arrangements) = self.constrain_start(puzzle);
+        let pieces = PieceSet::first(puzzle.pieces.len()).without(used_piece);
+
+        let mut units = vec![];
+        for start in starting_arrangements {
+            let Some(cube) = puzzle.full.difference(start.occupied).first() else {
+                units.push((start, vec![]));
+                continue;
+            };
+            for pid in pieces.iter() {
+                for &placement in &puzzle.lookup[cube][pid] {
+                    if !start.occupied.intersects(placement) {
+                        let mut unit = start.clone();
+                        unit.push(pid, placement);
+                        units.push((unit, pieces.without(pid).iter().collect()));
+                    }
+                }
+            }
+        }
+        units
+    }
+
+    /// Enumerates the solutions in one shard of the work units, like `begin`
+    pub fn begin_shard<const W: usize>(
+        &mut self,
+        puzzle: &Puzzle<W>,
+        strategy: &dyn SolveStrategy<W>,
+        shard: Shard,
+        found: &mut SolutionSink<W>,
+    ) -> ControlFlow<()> {
+        self.stats = Statistics::default();
+        self.durations.clear();
+        self.resume_shard(puzzle, strategy, shard, 0, found, &mut |_, _| ControlFlow::Continue(()))
+    }
+
+    /// Continues `begin_shard` after the first `completed` work units of the shard, adding to
+    /// `self.stats` and `self.durations`. `unit_done` is called with the number of work units of the shard completed,
+    /// and the statistics so far, whenever a unit is finished.
+    pub fn resume_shard<const W: usize>(
+        &mut self,
+        puzzle: &Puzzle<W>,
+        strategy: &dyn SolveStrategy<W>,
+        shard: Shard,
+        completed: usize,
+        found: &mut SolutionSink<W>,
+        unit_done: &mut dyn FnMut(usize, &Statistics) -> ControlFlow<()>,
+    ) -> ControlFlow<()> {
+        let units = self.work_units(puzzle);
+        let shard_units: Vec<_> = shard.units(units.len()).map(|unit| &units[unit]).collect();
+        let mut found = self.counted(found);
+        for (done, (start, remaining)) in shard_units.iter().enumerate().skip(completed) {
+            self.progress.begin_start(done, shard_units.len());
+            let start_time = Instant::now();
+            let flow = strategy.solve(puzzle, start, remaining, &mut found, &mut self.stats, &self.cancel, &self.progress);
+            self.durations.push(start_time.elapsed());
+            flow?;
+            unit_done(done + 1, &self.stats)?;
+        }
+        self.progress.begin_start(shard_units.len(), shard_units.len());
+        ControlFlow::Continue(())
+    }
+
+    /// Estimates the number of nodes and solutions of the search `backtracker` would make, and its
+    /// duration, from `probes` random probes of Knuth's estimator, each below a starting
+    /// arrangement chosen uniformly. The same seed gives the same estimate.
+    pub fn estimate<const W: usize>(
+        &self,
+        puzzle: &Puzzle<W>,
+        backtracker: &Backtracker,
+        probes: usize,
+        seed: u64,
+    ) -> Estimate {
+        let (used_piece, starting_arrangements) = self.constrain_start(puzzle);
+        let remaining = PieceSet::first(puzzle.pieces.len()).without(used_piece);
+        let order = backtracker.cell_sequence(puzzle);
+        let starts = starting_arrangements.len() as f64;
+
+        let mut random = Random::new(seed);
+        let mut nodes = vec![];
+        let mut solutions = vec![];
+        let mut seconds = vec![];
+        while nodes.len() < probes && !starting_arrangements.is_empty() && !self.cancel.is_cancelled() {
+            let start = &starting_arrangements[random.below(starting_arrangements.len())];
+            let (n, s, t) = backtracker.probe(puzzle, &order, Path::new(start), remaining, &mut random);
+            nodes.push(n * starts);
+            solutions.push(s * starts);
+            seconds.push(t * starts);
+        }
+
+        Estimate {
+            probes: nodes.len(),
+            nodes: Mean::of(&nodes),
+            solutions: Mean::of(&solutions),
+            seconds: Mean::of(&seconds),
+        }
+    }
+
+    /// Records the search `backtracker` makes, for drawing: below the root, one node for each
+    /// starting arrangement and below them one for each placement tried that does not overlap
+    /// the pieces already placed, down to `max_depth` pieces past the starting arrangement and up
+    /// to `max_nodes` nodes in all. Cancelling `self.cancel` stops it like the node limit.
+    pub fn trace<const W: usize>(
+        &self,
+        puzzle: &Puzzle<W>,
+        backtracker: &Backtracker,
+        max_depth: usize,
+        max_nodes: usize,
+    ) -> Trace {
+        let (used_piece, starting_arrangements) = self.constrain_start(puzzle);
+        let remaining = PieceSet::first(puzzle.pieces.len()).without(used_piece);
+        let order = backtracker.cell_sequence(puzzle);
+
+        let mut trace = Trace::new(max_nodes);
+        for start in &starting_arrangements {
+            let (piece, placement) = start.placements[start.placements.len() - 1];
+            let Some(node) = trace.push(0, piece, placement.ones().collect(), None) else {
+                break;
+            };
+            let mut path = Path::new(start);
+            let solutions = backtracker.trace_board(&mut trace, node, puzzle, &order, &mut path, 0, remaining, max_depth, &self.cancel);
+            trace.nodes[node].solutions = solutions;
+            trace.nodes[0].solutions += solutions;
+            if self.cancel.is_cancelled() {
+                trace.truncated = true;
+            }
+            if trace.truncated {
+                break;
+            }
+        }
+        trace
+    }
+
+    /// Sink that counts the solutions passed on to `found` in `self.progress`
+    fn counted<'a, 'b, const W: usize>(
+        &self,
+        found: &'a mut SolutionSink<'b, W>,
+    ) -> impl FnMut(&Solution<W>) -> ControlFlow<()> + use<'a, 'b, W> {
+        let progress = Arc::clone(&self.progress);
+        move |solution| {
+            progress.solutions.fetch_add(1, Ordering::Relaxed);
+            found(solution)
+        }
+    }
+
+    /// Collects every solution of the puzzle
+    pub fn solutions<const W: usize>(
+        &mut self,
+        puzzle: &Puzzle<W>,
+        strategy: &dyn SolveStrategy<W>,
+    ) -> Vec<Solution<W>> {
+        let mut solutions = vec![];
+        let _ = self.begin(puzzle, strategy, &mut |solution| {
+            solutions.push(solution.clone());
+            ControlFlow::Continue(())
+        });
+        solutions
     }
 }
@@ -0,0 +1,127 @@
+use crate::puzzle::MAX_PIECES;
+
+use std::io::{self, Write};
+use std::ops::AddAssign;
+use std::time::Duration;
+
+/// Counters collected while searching
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Statistics {
+    /// Search nodes visited
+    pub nodes: u64,
+    /// Search nodes visited up to and including the first solution
+    pub nodes_to_first_solution: Option<u64>,
+    /// Placements of a remaining piece the backtracker checked
+    pub placements: u64,
+    /// Placements rejected because they overlap a piece already placed
+    pub overlaps: u64,
+    /// Placements rejected because the remaining pieces could no longer cover some empty cell
+    pub uncovered_cells: u64,
+    /// Placements rejected because some remaining piece would no longer fit anywhere
+    pub unplaceable_pieces: u64,
+    /// Branches cut because a region of empty cells could not be filled by the remaining pieces
+    pub isolated_regions: u64,
+    /// Search nodes visited with each number of pieces placed
+    pub depth_nodes: [u64; MAX_PIECES + 1],
+}
+
+impl Default for Statistics {
+    fn default() -> Self {
+        Statistics {
+            nodes: 0,
+            nodes_to_first_solution: None,
+            placements: 0,
+            overlaps: 0,
+            uncovered_cells: 0,
+            unplaceable_pieces: 0,
+            isolated_regions: 0,
+            depth_nodes: [0; MAX_PIECES + 1],
+        }
+    }
+}
+
+impl AddAssign for Statistics {
+    fn add_assign(&mut self, other: Statistics) {
+        self.nodes += other.nodes;
+        self.nodes_to_first_solution = match (self.nodes_to_first_solution, other.nodes_to_first_solution) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.placements += other.placements;
+        self.overlaps += other.overlaps;
+        self.uncovered_cells += other.uncovered_cells;
+        self.unplaceable_pieces += other.unplaceable_pieces;
+        self.isolated_regions += other.isolated_regions;
+        for (nodes, other) in self.depth_nodes.iter_mut().zip(other.depth_nodes) {
+            *nodes += other;
+        }
+    }
+}
+
+impl Statistics {
+    /// Node counts from no pieces placed up to the deepest level reached
+    pub fn depths(&self) -> &[u64] {
+        let deepest = self.depth_nodes.iter().rposition(|&nodes| nodes > 0).map_or(0, |d| d + 1);
+        &self.depth_nodes[..deepest]
+    }
+
+    /// Placements rejected by each check of the backtracker, in the order they are made
+    pub fn rejections(&self) -> [(&'static str, u64); 4] {
+        [
+            ("overlap", self.overlaps),
+            ("uncovered cells", self.uncovered_cells),
+            ("unplaceable pieces", self.unplaceable_pieces),
+            ("isolated regions", self.isolated_regions),
+        ]
+    }
+}
+
+/// Detailed account of a search, for measuring what each check of the backtracker is worth
+pub struct Report<'a> {
+    pub stats: &'a Statistics,
+    /// Time spent on each starting arrangement, or each work unit of a shard
+    pub durations: &'a [Duration],
+}
+
+impl Report<'_> {
+    pub fn write_table(&self, out: &mut impl Write) -> io::Result<()> {
+        let stats = self.stats;
+        let share = |count: u64| 100.0 * count as f64 / stats.placements.max(1) as f64;
+        writeln!(out, "===== Report =====")?;
+        writeln!(out, "Placements Tried: {}", stats.placements)?;
+        for (check, rejected) in stats.rejections() {
+            writeln!(out, "Rejected, {}: {} ({:.1}%)", check, rejected, share(rejected))?;
+        }
+        writeln!(out, "\n{:>6}  {:>12}", "Pieces", "Nodes")?;
+        for (depth, nodes) in stats.depths().iter().enumerate() {
+            writeln!(out, "{:>6}  {:>12}", depth, nodes)?;
+        }
+        writeln!(out, "\n{:>6}  {:>12}", "Start", "Seconds")?;
+        for (start, duration) in self.durations.iter().enumerate() {
+            writeln!(out, "{:>6}  {:>12.3}", start + 1, duration.as_secs_f64())?;
+        }
+        Ok(())
+    }
+
+    /// Writes the report as one line of JSON
+    pub fn write_json(&self, out: &mut impl Write) -> io::Result<()> {
+        let stats = self.stats;
+        let list = |items: Vec<String>| items.join(",");
+        let rejected = stats
+            .rejections()
+            .iter()
+            .map(|(check, rejected)| format!("\"{}\":{}", check.replace(' ', "_"), rejected))
+            .collect();
+        let depths = stats.depths().iter().map(u64::to_string).collect();
+        let durations = self.durations.iter().map(|d| d.as_secs_f64().to_string()).collect();
+        writeln!(
+            out,
+            "{{\"nodes\":{},\"placements\":{},\"rejected\":{{{}}},\"depth_nodes\":[{}],\"start_seconds\":[{}]}}",
+            stats.nodes,
+            stats.placements,
+            list(rejected),
+            list(depths),
+            list(durations)
+        )
+    }
+}
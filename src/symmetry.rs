@@ -0,0 +1,99 @@
+use crate::puzzle::{Bitset, Coord};
+
+/// A symmetry of a box, as a signed permutation of the axes: axis `i` of the image is axis
+/// `axes[i]` of the original, reversed when `flip[i]` is set
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Symmetry {
+    axes: [usize; 3],
+    flip: [bool; 3],
+}
+
+impl Symmetry {
+    /// The 48 symmetries of a cube: the 24 rotations, starting with the identity, followed by
+    /// the 24 reflections
+    pub fn all() -> Vec<Symmetry> {
+        let mut symmetries = vec![];
+        for axes in [[0, 1, 2], [0, 2, 1], [1, 0, 2], [1, 2, 0], [2, 0, 1], [2, 1, 0]] {
+            for flips in 0..8 {
+                let flip = [flips & 1 != 0, flips & 2 != 0, flips & 4 != 0];
+                symmetries.push(Symmetry { axes, flip });
+            }
+        }
+        symmetries.sort_by_key(|symmetry| !symmetry.is_rotation());
+        symmetries
+    }
+
+    /// Whether the symmetry preserves handedness
+    pub fn is_rotation(&self) -> bool {
+        // Odd permutations swap exactly one pair of axes
+        let odd_permutation = (0..3).filter(|&i| self.axes[i] == i).count() == 1;
+        let odd_flips = self.flip.iter().filter(|&&f| f).count() % 2 == 1;
+        odd_permutation == odd_flips
+    }
+
+    /// Dimensions of the image of a box with dimensions `dim`
+    pub fn image_dim(&self, dim: Coord) -> Coord {
+        let sides = [dim.x, dim.y, dim.z];
+        Coord {
+            x: sides[self.axes[0]],
+            y: sides[self.axes[1]],
+            z: sides[self.axes[2]],
+        }
+    }
+
+    /// Image of a cell of a box with dimensions `dim`
+    pub fn apply(&self, coord: Coord, dim: Coord) -> Coord {
+        let sides = [dim.x, dim.y, dim.z];
+        let values = [coord.x, coord.y, coord.z];
+        let axis = |i: usize| {
+            let v = values[self.axes[i]];
+            if self.flip[i] {
+                sides[self.axes[i]] - 1 - v
+            } else {
+                v
+            }
+        };
+        Coord {
+            x: axis(0),
+            y: axis(1),
+            z: axis(2),
+        }
+    }
+
+    /// Where the symmetry sends each cell of a box with dimensions `dim`, if it maps the box
+    /// onto itself
+    pub fn cell_map(&self, dim: Coord) -> Option<CellMap> {
+        if self.image_dim(dim) != dim {
+            return None;
+        }
+        Some(CellMap {
+            cells: (0..dim.volume())
+                .map(|index| self.apply(Coord::from_index(index, dim), dim).to_index(dim))
+                .collect(),
+            reflection: !self.is_rotation(),
+        })
+    }
+}
+
+/// Permutation of the cells of a box induced by a `Symmetry`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CellMap {
+    cells: Vec<usize>,
+    reflection: bool,
+}
+
+impl CellMap {
+    /// Image of a set of cells
+    pub fn apply<const W: usize>(&self, cells: Bitset<W>) -> Bitset<W> {
+        let mut image = Bitset::new();
+        for cell in cells.ones() {
+            image.set(self.cells[cell]);
+        }
+        image
+    }
+
+    /// Whether the map turns pieces into their mirror images
+    pub fn is_reflection(&self) -> bool {
+        self.reflection
+    }
+}
@@ -0,0 +1,116 @@
+use crate::puzzle::Coord;
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// Shape the pieces have to fill, as a set of cells inside a box of dimensions `dim`
+#[derive(Clone, Debug)]
+pub struct Target {
+    pub dim: Coord,
+    pub cells: Vec<Coord>,
+}
+
+impl Target {
+    /// Target filling the whole box
+    pub fn cuboid(dim: Coord) -> Target {
+        let cells = (0..dim.volume()).map(|i| Coord::from_index(i, dim)).collect();
+        Target { dim, cells }
+    }
+
+    /// Reads a target from a layered ASCII grid. Each block of lines separated by a blank line is
+    /// one layer along z, each line in a block is a row along y and each character a cell along x.
+    /// `#` or `X` marks a cell of the shape, `.` or a space a cell outside it, e.g.
+    ///
+    /// ```text
+    /// ###
+    /// #.#
+    ///
+    /// ..#
+    /// ```
+    pub fn from_file(path: PathBuf) -> io::Result<Target> {
+        let contents = fs::read_to_string(&path)?;
+        Target::parse(&contents).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {e}", path.display()),
+            )
+        })
+    }
+
+    pub fn parse(s: &str) -> Result<Target, String> {
+        let mut cells = vec![];
+        let mut z = 0;
+        let mut y = 0;
+        for line in s.lines().map(|l| l.trim_end()) {
+            if line.is_empty() {
+                // Any number of blank lines ends the current layer
+                if y > 0 {
+                    z += 1;
+                    y = 0;
+                }
+                continue;
+            }
+            for (x, c) in line.chars().enumerate() {
+                match c {
+                    '#' | 'X' => cells.push(Coord::new(x, y, z)),
+                    '.' | ' ' => (),
+                    _ => return Err(format!("unexpected character {c:?} in target")),
+                }
+            }
+            y += 1;
+        }
+        if cells.is_empty() {
+            return Err("target has no cells".to_string());
+        }
+
+        // Shrink the box to the bounding box of the shape
+        let min = |f: fn(&Coord) -> i64| cells.iter().map(f).min().unwrap();
+        let max = |f: fn(&Coord) -> i64| cells.iter().map(f).max().unwrap();
+        let (min_x, min_y, min_z) = (min(|c| c.x), min(|c| c.y), min(|c| c.z));
+        let dim = Coord {
+            x: max(|c| c.x) - min_x + 1,
+            y: max(|c| c.y) - min_y + 1,
+            z: max(|c| c.z) - min_z + 1,
+        };
+        let cells = cells
+            .iter()
+            .map(|c| Coord {
+                x: c.x - min_x,
+                y: c.y - min_y,
+                z: c.z - min_z,
+            })
+            .collect();
+
+        Ok(Target { dim, cells })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_layers() {
+        let target = Target::parse("##\n#.\n\n\n.#\n..\n").unwrap();
+        assert_eq!(target.dim, Coord::new(2, 2, 2));
+        assert_eq!(
+            target.cells,
+            vec![Coord::new(0, 0, 0), Coord::new(1, 0, 0), Coord::new(0, 1, 0), Coord::new(1, 0, 1)]
+        );
+    }
+
+    #[test]
+    fn parse_shrinks_to_bounding_box() {
+        let target = Target::parse("....\n.XX.\n").unwrap();
+        assert_eq!(target.dim, Coord::new(2, 1, 1));
+        assert_eq!(target.cells, vec![Coord::new(0, 0, 0), Coord::new(1, 0, 0)]);
+    }
+
+    #[test]
+    fn parse_rejects_bad_targets() {
+        assert!(Target::parse("#?#\n").is_err());
+        assert!(Target::parse("...\n\n...\n").is_err());
+        assert!(Target::parse("").is_err());
+    }
+}
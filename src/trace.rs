@@ -0,0 +1,104 @@
+use crate::solver::Check;
+
+use std::io::{self, Write};
+
+/// Node of a traced search tree (see `Solver::trace`)
+#[derive(Clone, Debug, Default)]
+pub struct TraceNode {
+    pub parent: Option<usize>,
+    /// Piece placed at the node and the cells it covers, none for the root
+    pub piece: Option<usize>,
+    pub cells: Vec<usize>,
+    /// Check that rejected the placement, if any
+    pub pruned: Option<Check>,
+    /// Solutions found below the node
+    pub solutions: u64,
+    /// Whether the nodes below this one are in the trace, false at the depth limit
+    pub expanded: bool,
+}
+
+/// Search tree recorded for drawing, up to a maximum number of nodes
+#[derive(Clone, Debug)]
+pub struct Trace {
+    /// Nodes in the order they were visited, starting with the root
+    pub nodes: Vec<TraceNode>,
+    pub max_nodes: usize,
+    /// Whether the node limit or a cancellation cut the search short, so that some nodes are missing and the
+    /// solution counts are lower bounds
+    pub truncated: bool,
+}
+
+impl Trace {
+    pub fn new(max_nodes: usize) -> Trace {
+        Trace {
+            nodes: vec![TraceNode {
+                expanded: true,
+                ..TraceNode::default()
+            }],
+            max_nodes,
+            truncated: false,
+        }
+    }
+
+    /// Adds a child of `parent`, returning its index, or none once the trace is full
+    pub fn push(&mut self, parent: usize, piece: usize, cells: Vec<usize>, pruned: Option<Check>) -> Option<usize> {
+        if self.nodes.len() >= self.max_nodes {
+            self.truncated = true;
+            return None;
+        }
+        self.nodes.push(TraceNode {
+            parent: Some(parent),
+            piece: Some(piece),
+            cells,
+            pruned,
+            solutions: 0,
+            expanded: true,
+        });
+        Some(self.nodes.len() - 1)
+    }
+
+    /// Writes the tree as a Graphviz graph. Nodes with solutions below them are green, pruned
+    /// placements red with the check that rejected them, dead ends grey, and nodes whose children
+    /// are left out dashed.
+    pub fn write_dot(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "digraph search {{")?;
+        writeln!(out, "    node [shape=box, fontname=\"monospace\", style=filled, fillcolor=white];")?;
+        let mut leaves = vec![true; self.nodes.len()];
+        for node in &self.nodes {
+            if let Some(parent) = node.parent {
+                leaves[parent] = false;
+            }
+        }
+        for (i, node) in self.nodes.iter().enumerate() {
+            let mut label = match node.piece {
+                Some(piece) => {
+                    let cells: Vec<String> = node.cells.iter().map(usize::to_string).collect();
+                    format!("piece {piece}\\ncells {}", cells.join(" "))
+                }
+                None if self.truncated => format!("search\\n(first {} nodes)", self.nodes.len()),
+                None => "search".to_string(),
+            };
+            let style = match node.pruned {
+                Some(check) => {
+                    label += &format!("\\npruned: {check}");
+                    "color=red, fontcolor=red"
+                }
+                None if node.solutions > 0 => {
+                    if leaves[i] && node.expanded {
+                        label += "\\nsolution";
+                    } else {
+                        label += &format!("\\n{} solutions", node.solutions);
+                    }
+                    "fillcolor=palegreen"
+                }
+                None => "fillcolor=lightgrey",
+            };
+            let dashed = if node.expanded { "" } else { ", style=\"filled,dashed\"" };
+            writeln!(out, "    n{i} [label=\"{label}\", {style}{dashed}];")?;
+            if let Some(parent) = node.parent {
+                writeln!(out, "    n{parent} -> n{i};")?;
+            }
+        }
+        writeln!(out, "}}")
+    }
+}
@@ -0,0 +1,42 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use bedlam_cube::shard::SolutionFile;
+
+/// Runs the solver on the Soma cube and returns its output, checking that it succeeded
+fn soma(args: &[&str], checkpoint: &Path) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_bedlam-cube"))
+        .args(["puzzles/soma-cube.csv", "-s", "3x3x3", "--checkpoint"])
+        .arg(checkpoint)
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn total_solutions(output: &str) -> usize {
+    let line = output.lines().find_map(|line| line.strip_prefix("Total Solutions: ")).unwrap();
+    line.parse().unwrap()
+}
+
+#[test]
+fn resume_below_the_checkpoint_solution_count() {
+    let checkpoint: PathBuf = std::env::temp_dir().join(format!("bedlam-cube-checkpoint-{}", std::process::id()));
+    soma(&["--max-solutions", "300"], &checkpoint);
+    let saved = SolutionFile::read(checkpoint.clone()).unwrap();
+    assert!(saved.completed_units.is_some());
+
+    // The replayed solutions reach the limit before any work unit is searched, so the
+    // checkpoint is saved again as it was
+    let resume = ["--resume", checkpoint.to_str().unwrap()];
+    let output = soma(&[&resume[..], &["--max-solutions", "5"]].concat(), &checkpoint);
+    assert_eq!(total_solutions(&output), 5);
+    let resaved = SolutionFile::read(checkpoint.clone()).unwrap();
+    assert_eq!(resaved.solutions, saved.solutions);
+    assert_eq!(resaved.completed_units, saved.completed_units);
+
+    let output = soma(&resume, &checkpoint);
+    std::fs::remove_file(&checkpoint).unwrap();
+    assert_eq!(total_solutions(&output), 1292);
+}
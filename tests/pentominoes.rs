@@ -0,0 +1,33 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use bedlam_cube::puzzle::{Coord, Puzzle};
+use bedlam_cube::solver::{Algorithm, Solver};
+use bedlam_cube::target::Target;
+
+fn pentominoes(target: &Target) -> Puzzle<2> {
+    Puzzle::from_csv(PathBuf::from("puzzles/pentominoes.csv"), target, false).unwrap()
+}
+
+#[test]
+fn bitset_and_dlx_agree_on_the_holed_board() {
+    let target = Target::from_file(PathBuf::from("puzzles/pentominoes-8x8-hole.txt")).unwrap();
+    let puzzle = pentominoes(&target);
+    let mut bitset = Solver::build().solutions(&puzzle, Algorithm::Bitset.strategy().as_ref());
+    let mut dlx = Solver::build().solutions(&puzzle, Algorithm::Dlx.strategy().as_ref());
+    assert_eq!(bitset.len(), 91);
+    bitset.sort();
+    dlx.sort();
+    assert_eq!(bitset, dlx);
+}
+
+#[test]
+fn flat_box_has_528_solutions_up_to_rotation() {
+    // The sides of the 2x5x6 box all differ, so only the identity and the half turns about its
+    // three axes, 4 of the 24 rotations of a cube, map it onto itself
+    let puzzle = pentominoes(&Target::cuboid(Coord::new(6, 5, 2)));
+    assert_eq!(puzzle.symmetries.iter().filter(|map| !map.is_reflection()).count(), 4);
+    let solutions = Solver::build().solutions(&puzzle, Algorithm::Bitset.strategy().as_ref());
+    let classes: HashSet<_> = solutions.iter().map(|solution| solution.canonical(&puzzle, false)).collect();
+    assert_eq!(classes.len(), 528);
+}
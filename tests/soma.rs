@@ -0,0 +1,39 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use bedlam_cube::puzzle::{Coord, Puzzle};
+use bedlam_cube::solver::{Algorithm, Solver};
+use bedlam_cube::target::Target;
+
+fn soma() -> Puzzle {
+    Puzzle::from_csv(
+        PathBuf::from("puzzles/soma-cube.csv"),
+        &Target::cuboid(Coord::new(3, 3, 3)),
+        false,
+    )
+    .unwrap()
+}
+
+#[test]
+fn bitset_finds_every_soma_solution() {
+    let puzzle = soma();
+    let solutions = Solver::build().solutions(&puzzle, Algorithm::Bitset.strategy().as_ref());
+    assert_eq!(solutions.len(), 1292);
+}
+
+#[test]
+fn dlx_finds_every_soma_solution() {
+    let puzzle = soma();
+    let solutions = Solver::build().solutions(&puzzle, Algorithm::Dlx.strategy().as_ref());
+    assert_eq!(solutions.len(), 1292);
+}
+
+#[test]
+fn soma_has_480_solutions_up_to_rotation() {
+    let puzzle = soma();
+    let solutions = Solver::build().solutions(&puzzle, Algorithm::Bitset.strategy().as_ref());
+    let classes: HashSet<_> = solutions.iter().map(|solution| solution.canonical(&puzzle, false)).collect();
+    assert_eq!(classes.len(), 480);
+    let all: usize = classes.iter().map(|solution| solution.rotations(&puzzle).len()).sum();
+    assert_eq!(all, 11520);
+}
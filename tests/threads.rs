@@ -0,0 +1,28 @@
+use std::path::PathBuf;
+
+use bedlam_cube::puzzle::Puzzle;
+use bedlam_cube::solution::Solution;
+use bedlam_cube::solver::{Backtracker, Solver};
+use bedlam_cube::target::Target;
+
+/// Solutions of the holed pentomino board in the order the search passes them on, searched on a
+/// pool of `threads` threads
+fn solutions(threads: usize, split_depth: usize) -> Vec<Solution<2>> {
+    let target = Target::from_file(PathBuf::from("puzzles/pentominoes-8x8-hole.txt")).unwrap();
+    let puzzle: Puzzle<2> = Puzzle::from_csv(PathBuf::from("puzzles/pentominoes.csv"), &target, false).unwrap();
+    let backtracker = Backtracker {
+        split_depth,
+        ..Backtracker::default()
+    };
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+    pool.install(|| Solver::build().solutions(&puzzle, &backtracker))
+}
+
+#[test]
+fn same_solutions_in_the_same_order_for_any_thread_count() {
+    let sequential = solutions(1, 0);
+    assert_eq!(sequential.len(), 91);
+    assert_eq!(solutions(4, 0), sequential);
+    assert_eq!(solutions(1, 2), sequential);
+    assert_eq!(solutions(4, 2), sequential);
+}